@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, SegmentEval, TrackControl};
+
+const SAMPLES: usize = 10_000;
+
+fn segment() -> (TrackControl, TrackControl) {
+    (
+        TrackControl::new(Point3::new(0., 0., 0.), Vector3::new(10., 2., 3.), 0.),
+        TrackControl::new(Point3::new(20., 5., 7.), Vector3::new(5., -3., 10.), 1.),
+    )
+}
+
+fn spline(c: &mut Criterion) {
+    let (begin, end) = segment();
+    let mut group = c.benchmark_group("segment");
+    group.bench_function("spline", |b| {
+        b.iter(|| {
+            for n in 0..SAMPLES {
+                let i = n as f32 / SAMPLES as f32;
+                black_box(track::spline(&begin, &end, i));
+                black_box(track::spline_deriv(&begin, &end, i));
+            }
+        })
+    });
+    group.bench_function("segment_eval", |b| {
+        b.iter(|| {
+            let eval = SegmentEval::new(&begin, &end);
+            for n in 0..SAMPLES {
+                let i = n as f32 / SAMPLES as f32;
+                black_box(eval.eval(i));
+                black_box(eval.eval_deriv(i));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, spline);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::mesh;
+use twisty_beziers::track::{self, ArcLengthTable, TrackControl, TrackFollower};
+
+/// Segments in the representative track
+const SEGMENTS: usize = 16;
+
+/// Segments in the long track, enough for its mesh to need more than u16 indices
+const LONG_SEGMENTS: usize = 1000;
+
+/// Samples taken per segment by `sample_collection`
+const SAMPLES_PER_SEGMENT: usize = 100;
+
+/// A track snaking along +X, rising and falling and banking into each turn
+fn wavy_track(segments: usize) -> Vec<TrackControl> {
+    (0..=segments)
+        .map(|idx| {
+            let phase = idx as f32 * 0.7;
+            let position = Point3::new(idx as f32 * 20., phase.sin() * 8., phase.cos() * 15.);
+            let direction = Vector3::new(7., phase.cos() * 2., -phase.sin() * 4.);
+            TrackControl::new(position, direction, phase.sin() * 0.6)
+        })
+        .collect()
+}
+
+fn sampling(c: &mut Criterion) {
+    let controls = wavy_track(SEGMENTS);
+    let mut group = c.benchmark_group("sampling");
+    group.bench_function("sample_collection", |b| {
+        let total = SEGMENTS * SAMPLES_PER_SEGMENT;
+        b.iter(|| {
+            for n in 0..total {
+                let i = n as f32 / SAMPLES_PER_SEGMENT as f32;
+                black_box(track::sample_collection(&controls, i));
+            }
+        })
+    });
+    for &(name, segments) in &[("follower", SEGMENTS), ("follower_long", LONG_SEGMENTS)] {
+        let controls = wavy_track(segments);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for sample in TrackFollower::new(&controls, 0.5) {
+                    black_box(sample);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn arc_length(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arc_length_table");
+    for &segments in &[SEGMENTS, LONG_SEGMENTS] {
+        let controls = wavy_track(segments);
+        group.bench_with_input(BenchmarkId::new("new", segments), &controls, |b, controls| {
+            b.iter(|| black_box(ArcLengthTable::new(controls, 64)))
+        });
+    }
+    group.finish();
+}
+
+fn tessellation(c: &mut Criterion) {
+    let controls = wavy_track(SEGMENTS);
+    let mut group = c.benchmark_group("tessellation");
+    for &resolution in &[0.25, 0.5, 1.] {
+        group.bench_with_input(
+            BenchmarkId::new("track_tess_path", resolution),
+            &resolution,
+            |b, &resolution| {
+                b.iter(|| black_box(mesh::track_tess_path(&controls, 8, 8., resolution, None)))
+            },
+        );
+    }
+
+    // Far more vertices than a u16 can index, so the surface is split into chunks that fit
+    let long = wavy_track(LONG_SEGMENTS);
+    group.bench_function("mesh_chunker_long", |b| {
+        b.iter(|| {
+            for chunk in mesh::MeshChunker::new(&long, 8, 8., 0.5, u16::MAX as usize + 1) {
+                black_box(chunk);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sampling, arc_length, tessellation);
+criterion_main!(benches);
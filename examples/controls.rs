@@ -1,10 +1,17 @@
 use anyhow::Result;
 use klystron::{
-    runtime_2d::{event::WindowEvent, launch, App2D},
+    runtime_2d::{
+        event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent},
+        launch, App2D,
+    },
     DrawType, Engine, FramePacket, Matrix4, Object, Vertex, WinitBackend, UNLIT_FRAG, UNLIT_VERT,
 };
 use nalgebra::{Vector4, Vector3};
-use twisty_beziers::controls::{TwoAxisControls, GamepadAxes};
+use std::cell::Cell;
+use std::rc::Rc;
+use twisty_beziers::controls::{
+    Cursor, GamepadAxes, KeyboardAxes, Keys, MouseAxes, MouseMode, TwoAxisControls,
+};
 use wiiboard::WiiBoardRealtime;
 
 struct MyApp {
@@ -12,23 +19,40 @@ struct MyApp {
     dot: Object,
     cross: Object,
     controls: Box<dyn TwoAxisControls>,
+    keys: Option<Rc<Cell<Keys>>>,
+    cursor: Option<Rc<Cell<Cursor>>>,
 }
 
 const CROSS_SCALE: f32 = 0.1;
 const DOT_SCALE: f32 = 0.03;
 const BASE_SCALE: f32 = 0.5;
+const KEY_RAMP: f32 = 0.1;
+/// Size assumed for the window until its first `Resized` event
+const INITIAL_WINDOW_SIZE: (f32, f32) = (800., 600.);
 fn scale_mat(scale: f32) -> Matrix4<f32> {
     Matrix4::from_diagonal(&Vector4::new(scale, scale, scale, 1.))
 }
 
 impl App2D for MyApp {
     const TITLE: &'static str = "Controls debugger";
-    type Args = bool;
-
-    fn new(engine: &mut WinitBackend, wii: Self::Args) -> Result<Self> {
-        let controls: Box<dyn TwoAxisControls> = match wii {
-            true => Box::new(WiiBoardRealtime::new(5, 5)),
-            false => Box::new(GamepadAxes::new()?),
+    type Args = Option<String>;
+
+    fn new(engine: &mut WinitBackend, device: Self::Args) -> Result<Self> {
+        let mut keys = None;
+        let mut cursor = None;
+        let controls: Box<dyn TwoAxisControls> = match device.as_deref() {
+            None => Box::new(GamepadAxes::new()?),
+            Some("keyboard") => {
+                let keyboard = KeyboardAxes::new(KEY_RAMP);
+                keys = Some(keyboard.keys());
+                Box::new(keyboard)
+            }
+            Some("mouse") => {
+                let mouse = MouseAxes::new(MouseMode::Absolute, INITIAL_WINDOW_SIZE);
+                cursor = Some(mouse.cursor());
+                Box::new(mouse)
+            }
+            Some(_) => Box::new(WiiBoardRealtime::new(5, 5)),
         };
 
         // Bounding box
@@ -67,10 +91,33 @@ impl App2D for MyApp {
             material: tris,
         };
 
-        Ok(Self { bounds, dot, cross, controls })
+        Ok(Self {
+            bounds,
+            dot,
+            cross,
+            controls,
+            keys,
+            cursor,
+        })
     }
 
-    fn event(&mut self, _event: &WindowEvent, _engine: &mut WinitBackend) -> Result<()> {
+    fn event(&mut self, event: &WindowEvent, _engine: &mut WinitBackend) -> Result<()> {
+        if let (WindowEvent::KeyboardInput { input, .. }, Some(keys)) = (event, &self.keys) {
+            update_keys(keys, input);
+        }
+        if let Some(cursor) = &self.cursor {
+            let mut state = cursor.get();
+            match event {
+                WindowEvent::CursorMoved { position, .. } => {
+                    state.position = (position.x as f32, position.y as f32);
+                }
+                WindowEvent::Resized(size) => {
+                    state.window_size = (size.width as f32, size.height as f32);
+                }
+                _ => (),
+            }
+            cursor.set(state);
+        }
         Ok(())
     }
 
@@ -84,6 +131,18 @@ impl App2D for MyApp {
     }
 }
 
+fn update_keys(keys: &Cell<Keys>, input: &KeyboardInput) {
+    let pressed = input.state == ElementState::Pressed;
+    let mut state = keys.get();
+    match input.virtual_keycode {
+        Some(VirtualKeyCode::W) | Some(VirtualKeyCode::Up) => state.up = pressed,
+        Some(VirtualKeyCode::S) | Some(VirtualKeyCode::Down) => state.down = pressed,
+        Some(VirtualKeyCode::A) | Some(VirtualKeyCode::Left) => state.left = pressed,
+        Some(VirtualKeyCode::D) | Some(VirtualKeyCode::Right) => state.right = pressed,
+        _ => (),
+    }
+    keys.set(state);
+}
 
 fn square_verts(color: [f32; 3]) -> [Vertex; 4] {
     let vert = |x, y| Vertex::new([x, y, 0.], color);
@@ -108,6 +167,6 @@ fn cross(color: [f32; 3]) -> ([Vertex; 4], [u16; 4]) {
 
 fn main() -> Result<()> {
     let mut args = std::env::args().skip(1);
-    let wii = args.next().is_some();
-    launch::<MyApp>(wii)
+    let device = args.next();
+    launch::<MyApp>(device)
 }
@@ -0,0 +1,87 @@
+use crate::track::TrackSample;
+use nalgebra::{Matrix4, Point3, Vector3};
+
+/// View matrix for riding along the track at `sample`, looking forward with the track's up vector.
+/// `eye_offset` is in the cart's frame; x forward, y up and z to the right.
+pub fn ride_camera(sample: &TrackSample, eye_offset: Vector3<f32>) -> Matrix4<f32> {
+    let quat = sample.quaternion(&Vector3::x_axis());
+    let eye = sample.position + quat.transform_vector(&eye_offset);
+    let forward = quat.transform_vector(&Vector3::x());
+    let up = quat.transform_vector(&Vector3::y());
+    Matrix4::look_at_rh(&eye, &(eye + forward), &up)
+}
+
+/// A camera chasing a moving target on a critically damped spring. Its eye, forward and up
+/// vectors each carry a velocity, so it eases in and out of motion without overshooting, and the
+/// up vector never flips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaseCamera {
+    eye: Spring,
+    forward: Spring,
+    up: Spring,
+}
+
+impl ChaseCamera {
+    /// Create a new ChaseCamera at rest with the given transform (camera to world, looking down
+    /// -z)
+    pub fn new(transform: Matrix4<f32>) -> Self {
+        Self {
+            eye: Spring::at_rest(transform.transform_point(&Point3::origin()).coords),
+            forward: Spring::at_rest(transform.transform_vector(&-Vector3::z())),
+            up: Spring::at_rest(transform.transform_vector(&Vector3::y())),
+        }
+    }
+
+    /// Spring toward `target` for `dt` seconds and return the new transform (camera to world,
+    /// looking down -z). Higher `stiffness` follows more tightly; it is the spring's natural
+    /// frequency, in radians per second.
+    pub fn update(&mut self, target: &TrackSample, stiffness: f32, dt: f32) -> Matrix4<f32> {
+        let quat = target.quaternion(&Vector3::x_axis());
+        let forward = quat.transform_vector(&Vector3::x());
+        let up = quat.transform_vector(&Vector3::y());
+        self.eye.step(target.position.coords, stiffness, dt);
+        self.forward.step(forward, stiffness, dt);
+        self.up.step(up, stiffness, dt);
+        self.transform()
+    }
+
+    /// Current transform, from the camera to the world
+    pub fn transform(&self) -> Matrix4<f32> {
+        // Facing away from the target, so that -z points along `forward`
+        let eye = Point3::from(self.eye.value);
+        let forward = self.forward.value.normalize();
+        Matrix4::face_towards(&eye, &(eye - forward), &self.up.value.normalize())
+    }
+}
+
+impl Default for ChaseCamera {
+    fn default() -> Self {
+        Self::new(Matrix4::identity())
+    }
+}
+
+/// A critically damped spring's value and velocity
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Spring {
+    value: Vector3<f32>,
+    velocity: Vector3<f32>,
+}
+
+impl Spring {
+    fn at_rest(value: Vector3<f32>) -> Self {
+        Self {
+            value,
+            velocity: Vector3::zeros(),
+        }
+    }
+
+    /// Move toward `target` for `dt` seconds with natural frequency `omega`. The step is the
+    /// exact solution, so it stays stable for any `dt`.
+    fn step(&mut self, target: Vector3<f32>, omega: f32, dt: f32) {
+        let error = self.value - target;
+        let decay = (-omega * dt).exp();
+        let temp = (self.velocity + error * omega) * dt;
+        self.velocity = (self.velocity - temp * omega) * decay;
+        self.value = target + (error + temp) * decay;
+    }
+}
@@ -0,0 +1,135 @@
+use crate::track::{Track, TrackSample};
+use anyhow::{format_err, Result};
+use nalgebra::{Matrix4, Vector3};
+use std::str::FromStr;
+
+/// What a cart does when it reaches either end of the track
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum EndMode {
+    /// Wrap around to the other end, as on a closed track
+    #[default]
+    Loop,
+    /// Hold at the end
+    Stop,
+    /// Turn around and run back the way it came
+    Reverse,
+}
+
+impl FromStr for EndMode {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "loop" => Ok(Self::Loop),
+            "stop" => Ok(Self::Stop),
+            "reverse" => Ok(Self::Reverse),
+            _ => Err(format_err!("Unrecognized end mode {}", s)),
+        }
+    }
+}
+
+/// A cart riding along a track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cart {
+    /// Distance along the track
+    pub distance: f32,
+    /// Distance traveled per second
+    pub speed: f32,
+    /// Sideways offset from the center line, to the right
+    pub lateral: f32,
+    pub end_mode: EndMode,
+}
+
+impl Cart {
+    /// Create a new Cart `distance` along the track, on the center line
+    pub fn new(distance: f32, speed: f32) -> Self {
+        Self {
+            distance,
+            speed,
+            lateral: 0.,
+            end_mode: EndMode::default(),
+        }
+    }
+
+    /// Move sideways by `sensitivity * x`
+    pub fn steer(&mut self, x: f32, sensitivity: f32) {
+        self.lateral += sensitivity * x;
+    }
+
+    /// Move along `track` for `dt` seconds, handling its ends according to `end_mode`. A
+    /// reversing cart runs backwards with a negative speed.
+    pub fn advance(&mut self, track: &Track, dt: f32) {
+        let length = track.length();
+        self.distance += self.speed * dt;
+        if length <= 0. {
+            return;
+        }
+        match self.end_mode {
+            EndMode::Loop => self.distance = self.distance.rem_euclid(length),
+            EndMode::Stop => self.distance = self.distance.clamp(0., length),
+            EndMode::Reverse => {
+                if self.distance > length {
+                    self.distance = (2. * length - self.distance).max(0.);
+                    self.speed = -self.speed.abs();
+                } else if self.distance < 0. {
+                    self.distance = (-self.distance).min(length);
+                    self.speed = self.speed.abs();
+                }
+            }
+        }
+    }
+
+    /// Sample of the track under the cart's center line
+    pub fn sample(&self, track: &Track) -> Option<TrackSample> {
+        track.sample_at_distance(self.distance)
+    }
+
+    /// Transform from the cart's frame to the world; x forward, y up and z to the right
+    pub fn transform(&self, track: &Track) -> Option<Matrix4<f32>> {
+        let mut sample = self.sample(track)?;
+        sample.position += sample.right() * self.lateral;
+        Some(sample.transform(&Vector3::x()))
+    }
+}
+
+/// Ghost cart re-running a session recorded by `controls::Recorder`, steering with each logged
+/// sample's x axis once its time comes, as the live cart did once per frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayCart {
+    pub cart: Cart,
+    pub sensitivity: f32,
+    log: Vec<(f32, (f32, f32))>,
+    /// Index of the next log sample to steer with
+    next: usize,
+    time: f32,
+}
+
+impl ReplayCart {
+    /// Replay `log` on `cart`, steering with the same `sensitivity` as the recorded session
+    pub fn new(cart: Cart, log: Vec<(f32, (f32, f32))>, sensitivity: f32) -> Self {
+        Self {
+            cart,
+            sensitivity,
+            log,
+            next: 0,
+            time: 0.,
+        }
+    }
+
+    /// Move along `track` for `dt` seconds, steering with the samples logged in that time
+    pub fn update(&mut self, track: &Track, dt: f32) {
+        self.time += dt;
+        while let Some((time, (x, _))) = self.log.get(self.next) {
+            if *time > self.time {
+                break;
+            }
+            self.cart.steer(*x, self.sensitivity);
+            self.next += 1;
+        }
+        self.cart.advance(track, dt);
+    }
+
+    /// Whether every sample of the log has been replayed
+    pub fn finished(&self) -> bool {
+        self.next >= self.log.len()
+    }
+}
@@ -1,10 +1,116 @@
 use anyhow::{Result, format_err, Context};
-use gilrs::{Gilrs, GamepadId, Axis};
+#[cfg(feature = "gamepad")]
+use gilrs::{ff, Gilrs, GamepadId, Axis, Button, EventType};
+#[cfg(feature = "wiiboard")]
 use wiiboard::WiiBoardRealtime;
+use std::cell::Cell;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::net::{ToSocketAddrs, UdpSocket};
+#[cfg(feature = "websocket")]
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::rc::Rc;
+#[cfg(feature = "websocket")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "websocket")]
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(feature = "gamepad")]
+use std::time::SystemTime;
 
 pub trait TwoAxisControls {
     /// Sample the latest input and represented as two normalized axes
     fn axes(&mut self) -> Result<(f32, f32)>;
+
+    /// Sample the axes along with when the input was taken. Devices without their own timestamps
+    /// report the time of the call.
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        let (x, y) = self.axes()?;
+        Ok((x, y, Instant::now()))
+    }
+
+    /// Vibrate the device, if it is able to, with the strong and weak motors at [0..1] of their
+    /// full magnitude for `duration`
+    fn rumble(&mut self, _strong: f32, _weak: f32, _duration: Duration) -> Result<()> {
+        Ok(())
+    }
+
+    /// Re-zero the device and clear any filtering state, e.g. to recenter after drift
+    fn reset(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<C: TwoAxisControls + ?Sized> TwoAxisControls for Box<C> {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        (**self).axes()
+    }
+
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        (**self).axes_timed()
+    }
+
+    fn rumble(&mut self, strong: f32, weak: f32, duration: Duration) -> Result<()> {
+        (**self).rumble(strong, weak, duration)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        (**self).reset()
+    }
+}
+
+/// Implement `TwoAxisControls` methods for a wrapper by forwarding them to its `inner` source.
+/// `axes` covers both `axes()` and `axes_timed()`, passing each sample through the wrapper's
+/// `apply()`.
+macro_rules! forward_controls {
+    () => {};
+    (axes $(, $rest:ident)*) => {
+        fn axes(&mut self) -> Result<(f32, f32)> {
+            let axes = self.inner.axes()?;
+            Ok(self.apply(axes))
+        }
+
+        fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+            let (x, y, time) = self.inner.axes_timed()?;
+            let (x, y) = self.apply((x, y));
+            Ok((x, y, time))
+        }
+
+        forward_controls!($($rest),*);
+    };
+    (rumble $(, $rest:ident)*) => {
+        fn rumble(&mut self, strong: f32, weak: f32, duration: Duration) -> Result<()> {
+            self.inner.rumble(strong, weak, duration)
+        }
+
+        forward_controls!($($rest),*);
+    };
+    (reset $(, $rest:ident)*) => {
+        fn reset(&mut self) -> Result<()> {
+            self.inner.reset()
+        }
+
+        forward_controls!($($rest),*);
+    };
+}
+
+/// Digital buttons of an input device, separate from its axes. Buttons are named by gilrs, so
+/// this is only available with the `gamepad` feature.
+#[cfg(feature = "gamepad")]
+pub trait Buttons {
+    /// Whether `button` is currently held; always false for devices without it
+    fn button(&mut self, _button: Button) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl<C: Buttons + ?Sized> Buttons for Box<C> {
+    fn button(&mut self, button: Button) -> bool {
+        (**self).button(button)
+    }
 }
 
 pub struct Dummy;
@@ -14,52 +120,1315 @@ impl TwoAxisControls for Dummy {
     }
 }
 
+#[cfg(feature = "gamepad")]
+impl Buttons for Dummy {}
+
+/// Direction keys currently held on a keyboard
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Keys {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+/// Keyboard input, ramping toward the held direction. Key state is fed in from the window event
+/// loop through the handle returned by `keys()`.
+pub struct KeyboardAxes {
+    keys: Rc<Cell<Keys>>,
+    ramp: f32,
+    x: f32,
+    y: f32,
+}
+
+impl KeyboardAxes {
+    /// `ramp` is the furthest the axes move toward the held direction per call to `axes()`
+    pub fn new(ramp: f32) -> Self {
+        Self {
+            keys: Rc::new(Cell::new(Keys::default())),
+            ramp,
+            x: 0.,
+            y: 0.,
+        }
+    }
+
+    /// Shared handle to the key state, to be updated by the event loop
+    pub fn keys(&self) -> Rc<Cell<Keys>> {
+        self.keys.clone()
+    }
+}
+
+impl TwoAxisControls for KeyboardAxes {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let keys = self.keys.get();
+        let axis = |pos: bool, neg: bool| pos as i32 as f32 - neg as i32 as f32;
+        let mut target_x = axis(keys.right, keys.left);
+        let mut target_y = axis(keys.up, keys.down);
+
+        // Keep diagonals on the unit circle
+        let target_mag = target_x.hypot(target_y);
+        if target_mag > 1. {
+            target_x /= target_mag;
+            target_y /= target_mag;
+        }
+
+        // Ramp toward the target
+        let (dx, dy) = (target_x - self.x, target_y - self.y);
+        let dist = dx.hypot(dy);
+        let step = if dist > self.ramp {
+            self.ramp / dist
+        } else {
+            1.
+        };
+        self.x += dx * step;
+        self.y += dy * step;
+
+        Ok((self.x, self.y))
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.x = 0.;
+        self.y = 0.;
+        Ok(())
+    }
+}
+
+/// Cursor state within a window, in pixels
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Cursor {
+    pub position: (f32, f32),
+    pub window_size: (f32, f32),
+}
+
+/// How mouse input maps to the axes
+#[derive(Debug, Clone, Copy)]
+pub enum MouseMode {
+    /// Cursor position across the window
+    Absolute,
+    /// Accumulated cursor motion, `sensitivity` per pixel, clamped to [-1, 1]
+    Relative { sensitivity: f32 },
+}
+
+/// Mouse input. Cursor state is fed in from the window event loop through the handle returned
+/// by `cursor()`, including the window size whenever it changes.
+pub struct MouseAxes {
+    cursor: Rc<Cell<Cursor>>,
+    mode: MouseMode,
+    last_position: Option<(f32, f32)>,
+    x: f32,
+    y: f32,
+}
+
+impl MouseAxes {
+    /// Mouse input for a window that is initially `window_size` pixels, with the cursor taken to
+    /// start at its center
+    pub fn new(mode: MouseMode, window_size: (f32, f32)) -> Self {
+        let (width, height) = window_size;
+        let cursor = Cursor {
+            position: (width / 2., height / 2.),
+            window_size,
+        };
+        Self {
+            cursor: Rc::new(Cell::new(cursor)),
+            mode,
+            last_position: None,
+            x: 0.,
+            y: 0.,
+        }
+    }
+
+    /// Shared handle to the cursor state, to be updated by the event loop
+    pub fn cursor(&self) -> Rc<Cell<Cursor>> {
+        self.cursor.clone()
+    }
+}
+
+impl TwoAxisControls for MouseAxes {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let Cursor {
+            position: (px, py),
+            window_size: (width, height),
+        } = self.cursor.get();
+
+        match self.mode {
+            MouseMode::Absolute => {
+                if width > 0. && height > 0. {
+                    self.x = (px / width) * 2. - 1.;
+                    self.y = 1. - (py / height) * 2.;
+                }
+            }
+            MouseMode::Relative { sensitivity } => {
+                let (last_x, last_y) = self.last_position.unwrap_or((px, py));
+                self.x += (px - last_x) * sensitivity;
+                self.y -= (py - last_y) * sensitivity;
+                self.last_position = Some((px, py));
+            }
+        }
+
+        self.x = self.x.clamp(-1., 1.);
+        self.y = self.y.clamp(-1., 1.);
+        Ok((self.x, self.y))
+    }
+
+    /// Recenter relative mode on the current cursor position
+    fn reset(&mut self) -> Result<()> {
+        self.last_position = None;
+        self.x = 0.;
+        self.y = 0.;
+        Ok(())
+    }
+}
+
+/// Source of a single control axis on a gamepad
+#[cfg(feature = "gamepad")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadAxis {
+    Axis(Axis),
+    /// Right trigger minus left trigger, for throttle/brake style input
+    Triggers,
+}
+
+#[cfg(feature = "gamepad")]
+impl From<Axis> for GamepadAxis {
+    fn from(axis: Axis) -> Self {
+        GamepadAxis::Axis(axis)
+    }
+}
+
+#[cfg(feature = "gamepad")]
 pub struct GamepadAxes {
     gilrs: Gilrs,
     gamepad: GamepadId,
+    name: String,
+    connected: bool,
+    x_axis: GamepadAxis,
+    y_axis: GamepadAxis,
+    /// Rumble effect currently playing; it stops once dropped
+    effect: Option<ff::Effect>,
+    /// Time of the latest input event from the chosen gamepad
+    last_input: Option<SystemTime>,
 }
 
+#[cfg(feature = "gamepad")]
+fn init_gilrs() -> Result<Gilrs> {
+    Gilrs::new().map_err(|e| format_err!("gilrs failed to init {}", e))
+}
+
+#[cfg(feature = "gamepad")]
 impl GamepadAxes {
+    fn from_id(gilrs: Gilrs, gamepad: GamepadId) -> Self {
+        let name = gilrs.gamepad(gamepad).name().to_string();
+        Self {
+            gilrs,
+            gamepad,
+            name,
+            connected: true,
+            x_axis: Axis::LeftStickX.into(),
+            y_axis: Axis::LeftStickY.into(),
+            effect: None,
+            last_input: None,
+        }
+    }
+
+    /// Use the first connected gamepad
     pub fn new() -> Result<Self> {
-        let gilrs = Gilrs::new().map_err(|e| format_err!("gilrs failed to init {}", e))?;
-        let (gamepad, _) = gilrs.gamepads().next().context("No gamepads found")?;
-        Ok(Self { gilrs, gamepad })
+        Self::with_index(0)
+    }
+
+    /// Use the `n`th connected gamepad, as numbered by `list()`
+    pub fn with_index(n: usize) -> Result<Self> {
+        let gilrs = init_gilrs()?;
+        let count = gilrs.gamepads().count();
+        if count == 0 {
+            return Err(format_err!("No gamepads found"));
+        }
+        let (gamepad, _) = gilrs
+            .gamepads()
+            .nth(n)
+            .with_context(|| format!("Gamepad index {} out of range, {} connected", n, count))?;
+        Ok(Self::from_id(gilrs, gamepad))
+    }
+
+    /// Use the first gamepad whose name contains `substr`, ignoring case
+    pub fn with_name(substr: &str) -> Result<Self> {
+        let gilrs = init_gilrs()?;
+        let substr = substr.to_lowercase();
+        let found = gilrs
+            .gamepads()
+            .find(|(_, gamepad)| gamepad.name().to_lowercase().contains(&substr))
+            .map(|(id, _)| id);
+        match found {
+            Some(gamepad) => Ok(Self::from_id(gilrs, gamepad)),
+            None => {
+                let names: Vec<String> = gilrs
+                    .gamepads()
+                    .map(|(_, g)| g.name().to_string())
+                    .collect();
+                Err(format_err!(
+                    "No gamepad matching \"{}\", available: {:?}",
+                    substr,
+                    names
+                ))
+            }
+        }
+    }
+
+    /// Index and name of each connected gamepad
+    pub fn list() -> Result<Vec<(usize, String)>> {
+        let gilrs = init_gilrs()?;
+        Ok(gilrs
+            .gamepads()
+            .enumerate()
+            .map(|(idx, (_, gamepad))| (idx, gamepad.name().to_string()))
+            .collect())
+    }
+
+    /// Use the first connected gamepad, reading the given axes instead of the left stick
+    pub fn with_axes(x: impl Into<GamepadAxis>, y: impl Into<GamepadAxis>) -> Result<Self> {
+        let mut gamepad = Self::new()?;
+        gamepad.set_axes(x, y);
+        Ok(gamepad)
+    }
+
+    /// Choose which gamepad axes drive X and Y, e.g. `RightStickX`/`RightStickY`, `DPadX`/`DPadY`
+    /// or the triggers
+    pub fn set_axes(&mut self, x: impl Into<GamepadAxis>, y: impl Into<GamepadAxis>) {
+        self.x_axis = x.into();
+        self.y_axis = y.into();
+    }
+
+    /// Whether the chosen gamepad is currently connected
+    pub fn connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Process pending events, tracking disconnection of the chosen gamepad and re-acquiring one
+    /// with the same name when it is plugged back in
+    fn update(&mut self) {
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::Disconnected if event.id == self.gamepad => self.connected = false,
+                EventType::Connected
+                    if !self.connected && self.gilrs.gamepad(event.id).name() == self.name =>
+                {
+                    self.gamepad = event.id;
+                    self.connected = true;
+                }
+                EventType::AxisChanged(..) | EventType::ButtonChanged(..)
+                    if event.id == self.gamepad =>
+                {
+                    self.last_input = Some(event.time);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Value of an axis on the chosen gamepad. Many gamepads report the d-pad as buttons, so the
+    /// d-pad axes fall back to reading those.
+    fn axis_value(&self, axis: GamepadAxis) -> f32 {
+        let gamepad = self.gilrs.gamepad(self.gamepad);
+        let axis = match axis {
+            GamepadAxis::Axis(axis) => axis,
+            GamepadAxis::Triggers => {
+                // Triggers are analog buttons on most backends, and Z axes on the rest
+                let trigger = |button, axis| match gamepad.button_data(button) {
+                    Some(data) => data.value(),
+                    None => gamepad.axis_data(axis).map(|v| v.value()).unwrap_or(0.0),
+                };
+                let left = trigger(Button::LeftTrigger2, Axis::LeftZ);
+                let right = trigger(Button::RightTrigger2, Axis::RightZ);
+                return right - left;
+            }
+        };
+        if let Some(data) = gamepad.axis_data(axis) {
+            return data.value();
+        }
+        let buttons = |pos, neg| {
+            gamepad.is_pressed(pos) as i32 as f32 - gamepad.is_pressed(neg) as i32 as f32
+        };
+        match axis {
+            Axis::DPadX => buttons(Button::DPadRight, Button::DPadLeft),
+            Axis::DPadY => buttons(Button::DPadUp, Button::DPadDown),
+            _ => 0.0,
+        }
     }
 }
 
+#[cfg(feature = "gamepad")]
 impl TwoAxisControls for GamepadAxes {
     fn axes(&mut self) -> Result<(f32, f32)> {
-        self.gilrs.next_event();
-        self.gilrs.next_event();
-        let x = self
-            .gilrs
-            .gamepad(self.gamepad)
-            .axis_data(Axis::LeftStickX)
-            .map(|v| v.value())
-            .unwrap_or(0.0);
-        let y = self
-            .gilrs
-            .gamepad(self.gamepad)
-            .axis_data(Axis::LeftStickY)
-            .map(|v| v.value())
-            .unwrap_or(0.0);
-        Ok((x, y))
+        self.update();
+        if !self.connected {
+            return Ok((0., 0.));
+        }
+        Ok((self.axis_value(self.x_axis), self.axis_value(self.y_axis)))
+    }
+
+    /// Stamped with the time of the latest input event, so the time stays put while the gamepad
+    /// is untouched
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        let (x, y) = self.axes()?;
+        let now = Instant::now();
+        let time = self
+            .last_input
+            .and_then(|time| SystemTime::now().duration_since(time).ok())
+            .and_then(|age| now.checked_sub(age))
+            .unwrap_or(now);
+        Ok((x, y, time))
+    }
+
+    fn rumble(&mut self, strong: f32, weak: f32, duration: Duration) -> Result<()> {
+        self.update();
+        if !self.connected || !self.gilrs.gamepad(self.gamepad).is_ff_supported() {
+            return Ok(());
+        }
+
+        let magnitude = |m: f32| (m.clamp(0., 1.) * u16::MAX as f32) as u16;
+        let ticks = ff::Ticks::from_ms(duration.as_millis() as u32);
+        let scheduling = ff::Replay {
+            play_for: ticks,
+            ..Default::default()
+        };
+        let effect = ff::EffectBuilder::new()
+            .add_effect(ff::BaseEffect {
+                kind: ff::BaseEffectType::Strong {
+                    magnitude: magnitude(strong),
+                },
+                scheduling,
+                ..Default::default()
+            })
+            .add_effect(ff::BaseEffect {
+                kind: ff::BaseEffectType::Weak {
+                    magnitude: magnitude(weak),
+                },
+                scheduling,
+                ..Default::default()
+            })
+            .repeat(ff::Repeat::For(ticks))
+            .gamepads(&[self.gamepad])
+            .finish(&mut self.gilrs)
+            .map_err(|e| format_err!("Failed to create rumble effect {}", e))?;
+        effect
+            .play()
+            .map_err(|e| format_err!("Failed to play rumble effect {}", e))?;
+        self.effect = Some(effect);
+        Ok(())
     }
 }
 
+#[cfg(feature = "gamepad")]
+impl Buttons for GamepadAxes {
+    fn button(&mut self, button: Button) -> bool {
+        self.update();
+        self.connected && self.gilrs.gamepad(self.gamepad).is_pressed(button)
+    }
+}
+
+/// Total weight and the player's lean from a single poll of the board
+#[cfg(feature = "wiiboard")]
+struct BoardReading {
+    total: f32,
+    lean: Option<(f32, f32)>,
+}
+
+/// Poll the board, if there is new data. The lean is only present while someone is on the board.
+#[cfg(feature = "wiiboard")]
+fn poll_board(board: &mut WiiBoardRealtime) -> Result<Option<BoardReading>> {
+    Ok(board.poll()?.map(|data| {
+        let total = data.top_left + data.top_right + data.bottom_left + data.bottom_right;
+        let lean = if total > 0.0 {
+            let x = ((data.top_right + data.bottom_right) / total) * 2. - 1.;
+            let y = ((data.top_left + data.top_right) / total) * 2. - 1.;
+            //Some((x, y))
+            Some((y, x))
+        } else {
+            None
+        };
+        BoardReading { total, lean }
+    }))
+}
+
+#[cfg(feature = "wiiboard")]
 impl TwoAxisControls for WiiBoardRealtime {
     fn axes(&mut self) -> Result<(f32, f32)> {
-         if let Some(data) = self.poll()? {
-            let total = data.top_left + data.top_right + data.bottom_left + data.bottom_right;
-            if total > 0.0 {
-                let x = ((data.top_right + data.bottom_right) / total) * 2. - 1.;
-                let y = ((data.top_left + data.top_right) / total) * 2. - 1.;
-                //return Ok((x, y));
-                return Ok((y, x));
+        let lean = poll_board(self)?.and_then(|reading| reading.lean);
+        Ok(lean.unwrap_or((0., 0.)))
+    }
+}
+
+#[cfg(all(feature = "gamepad", feature = "wiiboard"))]
+impl Buttons for WiiBoardRealtime {}
+
+/// Wii balance board calibrated to the player's neutral stance and lean range
+#[cfg(feature = "wiiboard")]
+pub struct WiiBoard {
+    board: WiiBoardRealtime,
+    neutral: (f32, f32),
+    scale: (f32, f32),
+    weight: f32,
+    /// Take the next lean as the new neutral position
+    recenter: bool,
+    /// Total weight at or below which nobody is considered to be on the board, and the axes read
+    /// zero rather than sensor noise
+    pub presence_threshold: f32,
+    /// How long calibration waits for its readings before giving up
+    pub calibration_timeout: Duration,
+}
+
+/// Time to wait between polls of the board while calibrating, when it has nothing new
+#[cfg(feature = "wiiboard")]
+const BOARD_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+#[cfg(feature = "wiiboard")]
+impl WiiBoard {
+    /// Wrap a board, uncalibrated
+    pub fn new(board: WiiBoardRealtime) -> Self {
+        Self {
+            board,
+            neutral: (0., 0.),
+            scale: (1., 1.),
+            weight: 0.,
+            recenter: false,
+            presence_threshold: 0.,
+            calibration_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Whether someone was standing on the board, by `presence_threshold`, as of the latest
+    /// reading taken by `axes()` or `total_weight()`. Doesn't poll, so that it can't take a
+    /// reading away from `axes()`.
+    pub fn present(&self) -> bool {
+        self.weight > self.presence_threshold
+    }
+
+    /// Poll the board for the player's lean, keeping track of the total weight
+    fn poll(&mut self) -> Result<Option<(f32, f32)>> {
+        Ok(poll_board(&mut self.board)?.and_then(|reading| {
+            self.weight = reading.total;
+            reading.lean
+        }))
+    }
+
+    /// Sum of the four corner sensors as of the latest poll; near zero when nobody is on the board
+    pub fn total_weight(&mut self) -> Result<f32> {
+        self.poll()?;
+        Ok(self.weight)
+    }
+
+    /// Collect `samples` readings of the lean while someone is standing on the board, failing if
+    /// they haven't all arrived within `calibration_timeout`
+    fn collect(&mut self, samples: usize) -> Result<Vec<(f32, f32)>> {
+        let start = Instant::now();
+        let mut readings = Vec::with_capacity(samples);
+        while readings.len() < samples {
+            match self.poll()? {
+                Some(lean) => readings.push(lean),
+                None if start.elapsed() >= self.calibration_timeout => {
+                    return Err(format_err!(
+                        "Timed out after {:?} with {} of {} readings; is someone on the board?",
+                        self.calibration_timeout,
+                        readings.len(),
+                        samples
+                    ));
+                }
+                None => std::thread::sleep(BOARD_POLL_INTERVAL),
             }
         }
+        Ok(readings)
+    }
 
-        Ok((0., 0.))
+    /// Record the neutral standing position over `samples` readings, so that standing still maps
+    /// to (0, 0). Blocks until the player has been on the board for that long, or fails after
+    /// `calibration_timeout`.
+    pub fn calibrate(&mut self, samples: usize) -> Result<()> {
+        let readings = self.collect(samples)?;
+        let n = readings.len().max(1) as f32;
+        let (sum_x, sum_y) = readings
+            .iter()
+            .fold((0., 0.), |(ax, ay), (x, y)| (ax + x, ay + y));
+        self.neutral = (sum_x / n, sum_y / n);
+        Ok(())
+    }
+
+    /// Record the furthest lean from neutral over `samples` readings, while the player leans as
+    /// far as is comfortable in every direction, so that full lean maps to ±1.
+    /// Call after `calibrate()`.
+    pub fn calibrate_range(&mut self, samples: usize) -> Result<()> {
+        let (nx, ny) = self.neutral;
+        let readings = self.collect(samples)?;
+        let (max_x, max_y) = readings.iter().fold((0f32, 0f32), |(ax, ay), (x, y)| {
+            (ax.max((x - nx).abs()), ay.max((y - ny).abs()))
+        });
+        let inverse = |max: f32| if max > 0. { 1. / max } else { 1. };
+        self.scale = (inverse(max_x), inverse(max_y));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wiiboard")]
+impl TwoAxisControls for WiiBoard {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let lean = self.poll()?.filter(|_| self.weight > self.presence_threshold);
+        if let (true, Some(lean)) = (self.recenter, lean) {
+            self.neutral = lean;
+            self.recenter = false;
+        }
+        Ok(match lean {
+            Some((x, y)) => (
+                ((x - self.neutral.0) * self.scale.0).clamp(-1., 1.),
+                ((y - self.neutral.1) * self.scale.1).clamp(-1., 1.),
+            ),
+            None => (0., 0.),
+        })
+    }
+
+    /// Re-capture the neutral position from the next reading taken while someone is on the board,
+    /// keeping the lean range
+    fn reset(&mut self) -> Result<()> {
+        self.recenter = true;
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "gamepad", feature = "wiiboard"))]
+impl Buttons for WiiBoard {}
+
+/// Radial deadzone; input with a magnitude below `radius` reads as zero, and the remaining range
+/// is rescaled so that full deflection still reaches 1.0
+pub struct Deadzone<C> {
+    pub inner: C,
+    pub radius: f32,
+}
+
+impl<C: TwoAxisControls> Deadzone<C> {
+    /// Zero the axes within `radius` of center. Panics unless `radius` is in [0, 1).
+    pub fn new(inner: C, radius: f32) -> Self {
+        assert!(
+            (0. ..1.).contains(&radius),
+            "Deadzone radius must be in [0, 1), got {}",
+            radius
+        );
+        Self { inner, radius }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let magnitude = x.hypot(y);
+        if magnitude <= self.radius {
+            return (0., 0.);
+        }
+        let scale = (magnitude - self.radius) / (1. - self.radius) / magnitude;
+        (x * scale, y * scale)
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for Deadzone<C> {
+    forward_controls!(axes, rumble, reset);
+}
+
+/// Per-axis deadzone; each axis reads as zero until it passes its own deadzone, and is rescaled
+/// so that full deflection still reaches 1.0. Nest with `Deadzone` to combine both models.
+pub struct AxisDeadzone<C> {
+    pub inner: C,
+    pub deadzone_x: f32,
+    pub deadzone_y: f32,
+}
+
+impl<C: TwoAxisControls> AxisDeadzone<C> {
+    pub fn new(inner: C, deadzone_x: f32, deadzone_y: f32) -> Self {
+        Self {
+            inner,
+            deadzone_x,
+            deadzone_y,
+        }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            axis_deadzone(x, self.deadzone_x),
+            axis_deadzone(y, self.deadzone_y),
+        )
+    }
+}
+
+/// Threshold and rescale a single axis
+fn axis_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value.abs() <= deadzone {
+        return 0.;
+    }
+    (value.abs() - deadzone) / (1. - deadzone) * value.signum()
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for AxisDeadzone<C> {
+    forward_controls!(axes, rumble, reset);
+}
+
+/// Flip the sign of either axis
+pub struct Invert<C> {
+    pub inner: C,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl<C: TwoAxisControls> Invert<C> {
+    pub fn new(inner: C, invert_x: bool, invert_y: bool) -> Self {
+        Self {
+            inner,
+            invert_x,
+            invert_y,
+        }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let flip = |v: f32, invert: bool| if invert { -v } else { v };
+        (flip(x, self.invert_x), flip(y, self.invert_y))
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for Invert<C> {
+    forward_controls!(axes, rumble, reset);
+}
+
+/// Exponential moving average of the input. `alpha` is the weight given to each new sample, so
+/// smaller values are smoother but lag more.
+pub struct Smoothed<C> {
+    pub inner: C,
+    pub alpha: f32,
+    filtered: Option<(f32, f32)>,
+}
+
+impl<C: TwoAxisControls> Smoothed<C> {
+    pub fn new(inner: C, alpha: f32) -> Self {
+        Self {
+            inner,
+            alpha,
+            filtered: None,
+        }
+    }
+
+    fn apply(&mut self, (x, y): (f32, f32)) -> (f32, f32) {
+        let filtered = match self.filtered {
+            Some((fx, fy)) => (fx + (x - fx) * self.alpha, fy + (y - fy) * self.alpha),
+            None => (x, y),
+        };
+        self.filtered = Some(filtered);
+        filtered
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for Smoothed<C> {
+    forward_controls!(axes, rumble);
+
+    fn reset(&mut self) -> Result<()> {
+        self.filtered = None;
+        self.inner.reset()
+    }
+}
+
+/// Response curve for a single axis, mapping `v` to `sign(v) * |v|^exponent * gain`
+#[derive(Debug, Clone, Copy)]
+pub struct Curve {
+    pub gain: f32,
+    pub exponent: f32,
+}
+
+impl Curve {
+    pub fn new(gain: f32, exponent: f32) -> Self {
+        Self { gain, exponent }
+    }
+
+    /// Apply the curve, clamping the result to [-1, 1]
+    pub fn apply(&self, v: f32) -> f32 {
+        (v.signum() * v.abs().powf(self.exponent) * self.gain).clamp(-1., 1.)
+    }
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Self::new(1., 1.)
+    }
+}
+
+/// Apply a separate response curve to each axis
+pub struct ResponseCurve<C> {
+    pub inner: C,
+    pub x: Curve,
+    pub y: Curve,
+}
+
+impl<C: TwoAxisControls> ResponseCurve<C> {
+    pub fn new(inner: C, x: Curve, y: Curve) -> Self {
+        Self { inner, x, y }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (self.x.apply(x), self.y.apply(y))
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for ResponseCurve<C> {
+    forward_controls!(axes, rumble, reset);
+}
+
+/// Weighted blend of two control sources; a `weight` of 0 is all `a` and 1 is all `b`.
+/// If one source fails the other is used alone, and only if both fail is an error returned.
+pub struct Blend<A, B> {
+    pub a: A,
+    pub b: B,
+    pub weight: f32,
+}
+
+impl<A: TwoAxisControls, B: TwoAxisControls> Blend<A, B> {
+    pub fn new(a: A, b: B, weight: f32) -> Self {
+        Self { a, b, weight }
     }
 }
 
+impl<A: TwoAxisControls, B: TwoAxisControls> TwoAxisControls for Blend<A, B> {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        match (self.a.axes(), self.b.axes()) {
+            (Ok((ax, ay)), Ok((bx, by))) => {
+                let w = self.weight;
+                Ok((ax * (1. - w) + bx * w, ay * (1. - w) + by * w))
+            }
+            (Ok(a), Err(_)) => Ok(a),
+            (Err(_), Ok(b)) => Ok(b),
+            (Err(e), Err(_)) => Err(e),
+        }
+    }
+
+    fn rumble(&mut self, strong: f32, weak: f32, duration: Duration) -> Result<()> {
+        self.a.rumble(strong, weak, duration)?;
+        self.b.rumble(strong, weak, duration)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        self.a.reset()?;
+        self.b.reset()
+    }
+}
+
+/// Records every sample read through it as `(time, (x, y))`, with time in seconds since the
+/// recorder was created, as stamped by the source. Samples pass through unchanged.
+pub struct Recorder<C> {
+    pub inner: C,
+    start: Instant,
+    log: Vec<(f32, (f32, f32))>,
+}
+
+impl<C: TwoAxisControls> Recorder<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            start: Instant::now(),
+            log: Vec::new(),
+        }
+    }
+
+    /// Samples recorded so far
+    pub fn log(&self) -> &[(f32, (f32, f32))] {
+        &self.log
+    }
+
+    /// Write the log as CSV with a `time,x,y` header
+    pub fn write_csv(&self, mut writer: impl Write) -> Result<()> {
+        writeln!(writer, "time,x,y")?;
+        for (time, (x, y)) in &self.log {
+            writeln!(writer, "{},{},{}", time, x, y)?;
+        }
+        Ok(())
+    }
+
+    /// Write the log to a CSV file at `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create recording {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        self.write_csv(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for Recorder<C> {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let (x, y, _) = self.axes_timed()?;
+        Ok((x, y))
+    }
+
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        let (x, y, time) = self.inner.axes_timed()?;
+        let elapsed = time.saturating_duration_since(self.start).as_secs_f32();
+        self.log.push((elapsed, (x, y)));
+        Ok((x, y, time))
+    }
+
+    forward_controls!(rumble, reset);
+}
+
+/// Plays back a log recorded by `Recorder`, interpolating to the time since the first call to
+/// `axes()`. Once the log ends the last sample is held.
+pub struct Replay {
+    log: Vec<(f32, (f32, f32))>,
+    start: Option<Instant>,
+    elapsed: f32,
+}
+
+impl Replay {
+    pub fn new(log: Vec<(f32, (f32, f32))>) -> Self {
+        Self {
+            log,
+            start: None,
+            elapsed: 0.,
+        }
+    }
+
+    /// Recorded samples of `(time, (x, y))`
+    pub fn log(&self) -> &[(f32, (f32, f32))] {
+        &self.log
+    }
+
+    /// Read a log in the CSV format written by `Recorder::write_csv`
+    pub fn from_csv(reader: impl Read) -> Result<Self> {
+        let mut log = Vec::new();
+        for (idx, line) in BufReader::new(reader).lines().enumerate().skip(1) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let values = line
+                .split(',')
+                .map(|v| v.trim().parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .with_context(|| format!("Line {}: invalid number", idx + 1))?;
+            match values[..] {
+                [time, x, y] => log.push((time, (x, y))),
+                _ => return Err(format_err!("Line {}: expected time,x,y", idx + 1)),
+            }
+        }
+        Ok(Self::new(log))
+    }
+
+    /// Read a CSV log from the file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open recording {}", path.display()))?;
+        Self::from_csv(file)
+    }
+
+    /// Axes at `time` seconds into the log, linearly interpolated between samples
+    pub fn sample_at(&self, time: f32) -> (f32, f32) {
+        let next = self.log.partition_point(|(t, _)| *t <= time);
+        match (next.checked_sub(1).map(|i| self.log[i]), self.log.get(next)) {
+            (Some((t0, (x0, y0))), Some((t1, (x1, y1)))) => {
+                let i = (time - t0) / (t1 - t0);
+                (x0 + (x1 - x0) * i, y0 + (y1 - y0) * i)
+            }
+            (Some((_, axes)), None) => axes,
+            (None, Some((_, axes))) => *axes,
+            (None, None) => (0., 0.),
+        }
+    }
+
+    /// Whether playback has passed the last sample
+    pub fn finished(&self) -> bool {
+        self.log.last().is_none_or(|(t, _)| self.elapsed >= *t)
+    }
+}
+
+impl TwoAxisControls for Replay {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.elapsed = start.elapsed().as_secs_f32();
+        Ok(self.sample_at(self.elapsed))
+    }
+}
+
+/// Limits the input to the unit circle while preserving its direction, so that diagonals are no
+/// faster than straight lines
+pub struct ClampCircle<C> {
+    pub inner: C,
+}
+
+impl<C: TwoAxisControls> ClampCircle<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+
+    fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let magnitude = x.hypot(y);
+        if magnitude > 1. {
+            (x / magnitude, y / magnitude)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for ClampCircle<C> {
+    forward_controls!(axes, rumble, reset);
+}
+
+/// Time source for `Scripted`
+enum ScriptClock {
+    /// Seconds since the first call to `axes()`
+    Wall(Option<Instant>),
+    /// Advances by `dt` seconds per call to `axes()`, for deterministic runs
+    Fixed { dt: f32, time: f32 },
+}
+
+/// Programmatic input as a function of time in seconds, e.g. `|t| (t.sin(), 0.)` for a sweep
+pub struct Scripted {
+    script: Box<dyn FnMut(f32) -> (f32, f32)>,
+    clock: ScriptClock,
+}
+
+impl Scripted {
+    pub fn new(script: impl FnMut(f32) -> (f32, f32) + 'static) -> Self {
+        Self {
+            script: Box::new(script),
+            clock: ScriptClock::Wall(None),
+        }
+    }
+
+    /// Interpolate linearly between `(time, (x, y))` keyframes, sorted by time
+    pub fn keyframes(keyframes: Vec<(f32, (f32, f32))>) -> Self {
+        let replay = Replay::new(keyframes);
+        Self::new(move |time| replay.sample_at(time))
+    }
+
+    /// Advance time by a fixed `dt` per call instead of following the wall clock
+    pub fn fixed_step(mut self, dt: f32) -> Self {
+        self.clock = ScriptClock::Fixed { dt, time: 0. };
+        self
+    }
+}
+
+impl TwoAxisControls for Scripted {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let time = match &mut self.clock {
+            ScriptClock::Wall(start) => start
+                .get_or_insert_with(Instant::now)
+                .elapsed()
+                .as_secs_f32(),
+            ScriptClock::Fixed { dt, time } => {
+                let now = *time;
+                *time += *dt;
+                now
+            }
+        };
+        Ok((self.script)(time))
+    }
+}
+
+/// Axis swaps and sign flips for a device's mounting or orientation. The swap is applied before
+/// the inversions, so `invert_x` always refers to the output X axis.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct AxisMap {
+    pub swap_xy: bool,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl AxisMap {
+    pub fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (x, y) = if self.swap_xy { (y, x) } else { (x, y) };
+        let flip = |v: f32, invert: bool| if invert { -v } else { v };
+        (flip(x, self.invert_x), flip(y, self.invert_y))
+    }
+}
+
+/// Remap the axes of a control source according to an `AxisMap`
+pub struct Remap<C> {
+    pub inner: C,
+    pub map: AxisMap,
+}
+
+impl<C: TwoAxisControls> Remap<C> {
+    pub fn new(inner: C, map: AxisMap) -> Self {
+        Self { inner, map }
+    }
+
+    fn apply(&self, axes: (f32, f32)) -> (f32, f32) {
+        self.map.apply(axes)
+    }
+}
+
+impl<C: TwoAxisControls> TwoAxisControls for Remap<C> {
+    forward_controls!(axes, rumble, reset);
+}
+
+/// Several control sources at once, where whichever is pushed furthest wins each sample.
+/// Sources that fail are skipped for that sample.
+pub struct Merge {
+    pub sources: Vec<Box<dyn TwoAxisControls>>,
+}
+
+impl Merge {
+    pub fn new(sources: Vec<Box<dyn TwoAxisControls>>) -> Self {
+        Self { sources }
+    }
+}
+
+impl TwoAxisControls for Merge {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        Ok(self
+            .sources
+            .iter_mut()
+            .filter_map(|source| source.axes().ok())
+            .fold((0., 0.), |best, (x, y)| {
+                if x.hypot(y) > best.0.hypot(best.1) {
+                    (x, y)
+                } else {
+                    best
+                }
+            }))
+    }
+
+    fn rumble(&mut self, strong: f32, weak: f32, duration: Duration) -> Result<()> {
+        for source in &mut self.sources {
+            source.rumble(strong, weak, duration)?;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        for source in &mut self.sources {
+            source.reset()?;
+        }
+        Ok(())
+    }
+}
+
+/// Port `UdpControls` listens on by default
+pub const UDP_DEFAULT_PORT: u16 = 7878;
+
+/// Axes sent over the network. Each datagram is eight bytes: X then Y, as little-endian `f32`s.
+/// Datagrams of any other length are ignored. The latest datagram wins, and the axes read zero
+/// once none has arrived for `timeout`.
+pub struct UdpControls {
+    socket: UdpSocket,
+    pub timeout: Duration,
+    latest: Option<((f32, f32), Instant)>,
+}
+
+impl UdpControls {
+    /// Listen on `addr`, e.g. `("0.0.0.0", UDP_DEFAULT_PORT)`
+    pub fn new(addr: impl ToSocketAddrs, timeout: Duration) -> Result<Self> {
+        let socket = UdpSocket::bind(addr).context("Failed to bind UDP socket")?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            timeout,
+            latest: None,
+        })
+    }
+
+    /// Drain pending datagrams, keeping the newest valid one
+    fn receive(&mut self) -> Result<()> {
+        let latest = &mut self.latest;
+        drain_udp(&self.socket, &mut [0u8; 16], |datagram| {
+            if datagram.len() == 8 {
+                let value = |bytes: &[u8]| f32::from_le_bytes(bytes.try_into().unwrap());
+                let axes = (value(&datagram[..4]), value(&datagram[4..]));
+                *latest = Some((axes, Instant::now()));
+            }
+        })
+    }
+}
+
+/// Pass each datagram waiting on a non-blocking `socket` to `handle`, using `buf` to receive them
+fn drain_udp(socket: &UdpSocket, buf: &mut [u8], mut handle: impl FnMut(&[u8])) -> Result<()> {
+    loop {
+        match socket.recv(buf) {
+            Ok(len) => handle(&buf[..len]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(e).context("Failed to receive from UDP socket"),
+        }
+    }
+}
+
+impl TwoAxisControls for UdpControls {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let (x, y, _) = self.axes_timed()?;
+        Ok((x, y))
+    }
+
+    /// Stamped with the arrival of the latest datagram
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        self.receive()?;
+        Ok(match self.latest {
+            Some(((x, y), time)) if time.elapsed() <= self.timeout => (x, y, time),
+            _ => (0., 0., Instant::now()),
+        })
+    }
+}
+
+/// Open Sound Control input, e.g. from TouchOSC. Listens for float or integer messages on
+/// `x_address` and `y_address`, including within bundles, and maps `range` onto [-1, 1]. Each
+/// axis reads zero until its first message arrives, then holds the latest value.
+#[cfg(feature = "osc")]
+pub struct OscControls {
+    socket: UdpSocket,
+    pub x_address: String,
+    pub y_address: String,
+    /// Input values mapping to -1 and 1
+    pub range: (f32, f32),
+    x: f32,
+    y: f32,
+    last_message: Option<Instant>,
+}
+
+#[cfg(feature = "osc")]
+impl OscControls {
+    /// Listen on `addr`, e.g. `("0.0.0.0", 8000)`
+    pub fn new(
+        addr: impl ToSocketAddrs,
+        x_address: impl Into<String>,
+        y_address: impl Into<String>,
+        range: (f32, f32),
+    ) -> Result<Self> {
+        let socket = UdpSocket::bind(addr).context("Failed to bind OSC socket")?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            x_address: x_address.into(),
+            y_address: y_address.into(),
+            range,
+            x: 0.,
+            y: 0.,
+            last_message: None,
+        })
+    }
+
+    /// Apply a packet's messages for either axis; anything else is ignored
+    fn handle(&mut self, packet: rosc::OscPacket) {
+        match packet {
+            rosc::OscPacket::Message(message) => {
+                let value = match message.args.first() {
+                    Some(rosc::OscType::Float(v)) => *v,
+                    Some(rosc::OscType::Double(v)) => *v as f32,
+                    Some(rosc::OscType::Int(v)) => *v as f32,
+                    Some(rosc::OscType::Long(v)) => *v as f32,
+                    _ => return,
+                };
+                let (min, max) = self.range;
+                let value = ((value - min) / (max - min) * 2. - 1.).clamp(-1., 1.);
+                if message.addr == self.x_address {
+                    self.x = value;
+                } else if message.addr == self.y_address {
+                    self.y = value;
+                } else {
+                    return;
+                }
+                self.last_message = Some(Instant::now());
+            }
+            rosc::OscPacket::Bundle(bundle) => {
+                for packet in bundle.content {
+                    self.handle(packet);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "osc")]
+impl TwoAxisControls for OscControls {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let (x, y, _) = self.axes_timed()?;
+        Ok((x, y))
+    }
+
+    /// Stamped with the arrival of the latest message for either axis
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        let mut packets = Vec::new();
+        drain_udp(&self.socket, &mut [0u8; rosc::decoder::MTU], |datagram| {
+            // Malformed packets are dropped, as UDP may drop any packet anyway
+            if let Ok((_, packet)) = rosc::decoder::decode_udp(datagram) {
+                packets.push(packet);
+            }
+        })?;
+        for packet in packets {
+            self.handle(packet);
+        }
+        Ok((self.x, self.y, self.last_message.unwrap_or_else(Instant::now)))
+    }
+}
+
+/// Port `WebSocketControls` listens on by default
+#[cfg(feature = "websocket")]
+pub const WEBSOCKET_DEFAULT_PORT: u16 = 9001;
+
+/// Axes sent from a web page over WebSocket. Each text message is a JSON object with numeric `x`
+/// and `y` fields in [-1, 1], e.g. `{"x": 0.5, "y": -0.25}`; other messages are ignored. Any
+/// number of clients may connect, and the latest message from any of them wins. The axes read
+/// zero until the first message arrives.
+///
+/// The server runs on background threads for as long as the process does.
+#[cfg(feature = "websocket")]
+pub struct WebSocketControls {
+    latest: Arc<LatestAxes>,
+}
+
+/// Latest axes received by a background thread, and when they arrived
+#[cfg(feature = "websocket")]
+type LatestAxes = Mutex<Option<((f32, f32), Instant)>>;
+
+#[cfg(feature = "websocket")]
+impl WebSocketControls {
+    /// Listen on `addr`, e.g. `("0.0.0.0", WEBSOCKET_DEFAULT_PORT)`
+    pub fn new(addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr).context("Failed to bind WebSocket listener")?;
+        let latest = Arc::new(Mutex::new(None));
+        let shared = latest.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = shared.clone();
+                thread::spawn(move || serve_websocket(stream, &shared));
+            }
+        });
+        Ok(Self { latest })
+    }
+}
+
+/// Read messages from a single WebSocket client until it disconnects
+#[cfg(feature = "websocket")]
+fn serve_websocket(stream: TcpStream, latest: &LatestAxes) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    while let Ok(message) = socket.read_message() {
+        if let tungstenite::Message::Text(text) = message {
+            if let Some(axes) = parse_axes_json(&text) {
+                *latest.lock().unwrap() = Some((axes, Instant::now()));
+            }
+        }
+    }
+}
+
+/// Axes from a `{"x": .., "y": ..}` message, clamped to [-1, 1]
+#[cfg(feature = "websocket")]
+fn parse_axes_json(text: &str) -> Option<(f32, f32)> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let axis = |name| Some((value.get(name)?.as_f64()? as f32).clamp(-1., 1.));
+    Some((axis("x")?, axis("y")?))
+}
+
+#[cfg(feature = "websocket")]
+impl TwoAxisControls for WebSocketControls {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        let (x, y, _) = self.axes_timed()?;
+        Ok((x, y))
+    }
+
+    /// Stamped with the arrival of the latest message
+    fn axes_timed(&mut self) -> Result<(f32, f32, Instant)> {
+        Ok(match *self.latest.lock().unwrap() {
+            Some(((x, y), time)) => (x, y, time),
+            None => (0., 0., Instant::now()),
+        })
+    }
+}
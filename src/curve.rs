@@ -0,0 +1,502 @@
+use nalgebra::{Matrix4, Point3, RealField, Unit, UnitQuaternion, Vector3};
+
+/// Control point for a track, generic over the scalar type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackControl<T: RealField = f32> {
+    pub position: Point3<T>,
+    pub direction: Vector3<T>,
+    pub angle: T,
+    /// Bank from the previous control's angle to this one literally, e.g. for a full barrel roll,
+    /// rather than along the shortest arc
+    pub full_roll: bool,
+    /// Width of the track at this control
+    pub width: T,
+    /// Length of the handle behind this control, as a multiple of `direction`
+    pub in_length: T,
+    /// Length of the handle in front of this control, as a multiple of `direction`
+    pub out_length: T,
+    /// Weight of both handles of this control. Weights other than 1 make the segments on either
+    /// side rational, e.g. for exact circular arcs
+    pub weight: T,
+    /// Color of the track at this control
+    pub color: [T; 3],
+}
+
+/// Single precision track control, as used throughout `track`
+pub type TrackControlF32 = TrackControl<f32>;
+
+/// Double precision track control
+pub type TrackControlF64 = TrackControl<f64>;
+
+impl<T: RealField> TrackControl<T> {
+    /// Create a new TrackControl
+    pub fn new(position: Point3<T>, direction: Vector3<T>, angle: T) -> Self {
+        Self {
+            position,
+            direction,
+            angle,
+            full_roll: false,
+            width: T::zero(),
+            in_length: T::one(),
+            out_length: T::one(),
+            weight: T::one(),
+            color: [T::one(); 3],
+        }
+    }
+
+    /// Build a TrackControl incrementally
+    pub fn builder() -> TrackControlBuilder<T> {
+        TrackControlBuilder::new()
+    }
+
+    /// The control point in front of this track control
+    pub fn front_ctrlp(&self) -> Point3<T> {
+        self.position + self.direction * self.out_length
+    }
+
+    /// The control point behind this track control
+    pub fn back_ctrlp(&self) -> Point3<T> {
+        self.position - self.direction * self.in_length
+    }
+}
+
+impl<T: RealField> Default for TrackControl<T> {
+    /// At the origin facing +X, with no banking
+    fn default() -> Self {
+        Self::new(Point3::origin(), Vector3::x(), T::zero())
+    }
+}
+
+/// Builder for a TrackControl, starting from the default
+pub struct TrackControlBuilder<T: RealField = f32> {
+    control: TrackControl<T>,
+}
+
+impl<T: RealField> TrackControlBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            control: TrackControl::default(),
+        }
+    }
+
+    pub fn position(mut self, position: Point3<T>) -> Self {
+        self.control.position = position;
+        self
+    }
+
+    pub fn direction(mut self, direction: Vector3<T>) -> Self {
+        self.control.direction = direction;
+        self
+    }
+
+    /// Banking angle in radians
+    pub fn angle(mut self, angle: T) -> Self {
+        self.control.angle = angle;
+        self
+    }
+
+    /// Banking angle in degrees
+    pub fn angle_degrees(self, degrees: T) -> Self {
+        self.angle(degrees * T::pi() / real(180.))
+    }
+
+    pub fn full_roll(mut self, full_roll: bool) -> Self {
+        self.control.full_roll = full_roll;
+        self
+    }
+
+    pub fn width(mut self, width: T) -> Self {
+        self.control.width = width;
+        self
+    }
+
+    /// Length of the handle behind the control, as a multiple of the direction
+    pub fn in_length(mut self, in_length: T) -> Self {
+        self.control.in_length = in_length;
+        self
+    }
+
+    /// Length of the handle in front of the control, as a multiple of the direction
+    pub fn out_length(mut self, out_length: T) -> Self {
+        self.control.out_length = out_length;
+        self
+    }
+
+    /// Weight of both handles of the control
+    pub fn weight(mut self, weight: T) -> Self {
+        self.control.weight = weight;
+        self
+    }
+
+    /// Color at the control, white by default
+    pub fn color(mut self, color: [T; 3]) -> Self {
+        self.control.color = color;
+        self
+    }
+
+    pub fn build(self) -> TrackControl<T> {
+        self.control
+    }
+}
+
+impl<T: RealField> Default for TrackControlBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sample of a track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackSample<T: RealField = f32> {
+    pub position: Point3<T>,
+    pub derivative: Vector3<T>,
+    pub angle: T,
+    pub index: T,
+    /// Color interpolated between the controls, eased like the banking angle
+    pub color: [T; 3],
+}
+
+/// Single precision track sample
+pub type TrackSampleF32 = TrackSample<f32>;
+
+/// Double precision track sample
+pub type TrackSampleF64 = TrackSample<f64>;
+
+impl<T: RealField> TrackSample<T> {
+    pub fn quaternion(&self, axis: &Vector3<T>) -> UnitQuaternion<T> {
+        orientation(&self.derivative, self.angle, axis)
+    }
+
+    /// Position and orientation as a homogeneous matrix, taking `forward_axis` onto the track's
+    /// direction
+    pub fn transform(&self, forward_axis: &Vector3<T>) -> Matrix4<T> {
+        let rotation = self.quaternion(forward_axis).to_homogeneous();
+        Matrix4::new_translation(&self.position.coords) * rotation
+    }
+
+    /// Like `transform`, but facing back along the track, e.g. when traveling with a negative
+    /// rate. The up vector is kept, so the banking is still correct for a rider facing backward.
+    pub fn transform_backward(&self, forward_axis: &Vector3<T>) -> Matrix4<T> {
+        let quat = self.quaternion(forward_axis);
+        let local_up = Unit::new_normalize(quat.inverse_transform_vector(&self.up()));
+        let rotation = quat * UnitQuaternion::from_axis_angle(&local_up, T::pi());
+        Matrix4::new_translation(&self.position.coords) * rotation.to_homogeneous()
+    }
+
+    /// Lateral direction across the track surface, banked with the track
+    pub fn right(&self) -> Vector3<T> {
+        self.quaternion(&Vector3::x()).transform_vector(&Vector3::z())
+    }
+
+    /// Normal of the track surface, banked with the track
+    pub fn up(&self) -> Vector3<T> {
+        self.quaternion(&Vector3::x()).transform_vector(&Vector3::y())
+    }
+}
+
+/// Rotation taking `axis` onto `derivative`, then banked by `angle` about it. When `derivative`
+/// points straight back along `axis`, the turn is made about up (y) so the track stays upright.
+pub(crate) fn orientation<T: RealField>(
+    derivative: &Vector3<T>,
+    angle: T,
+    axis: &Vector3<T>,
+) -> UnitQuaternion<T> {
+    let turn = UnitQuaternion::rotation_between(axis, derivative).unwrap_or_else(|| {
+        // Any axis perpendicular to `axis` works for a half turn; prefer up, then z
+        let perpendicular = |v: Vector3<T>| {
+            let v = v - axis * (axis.dot(&v) / axis.norm_squared());
+            Unit::try_new(v, T::default_epsilon())
+        };
+        let about = perpendicular(Vector3::y())
+            .or_else(|| perpendicular(Vector3::z()))
+            .expect("Orientation axis must be nonzero");
+        UnitQuaternion::from_axis_angle(&about, T::pi())
+    });
+    UnitQuaternion::from_axis_angle(&Unit::new_normalize(*derivative), angle) * turn
+}
+
+/// Smoothe step from [0..1] to [0..1]
+pub fn smooth_step<T: RealField>(i: T) -> T {
+    i * i * (real::<T>(3.) - real::<T>(2.) * i)
+}
+
+/// Smoother step from [0..1] to [0..1], with zero second derivative at the ends
+pub fn smoother_step<T: RealField>(i: T) -> T {
+    i * i * i * (i * (i * real(6.) - real(15.)) + real(10.))
+}
+
+/// Sinusoidal ease in and out from [0..1] to [0..1]
+pub fn ease_in_out<T: RealField>(i: T) -> T {
+    (T::one() - (i * T::pi()).cos()) / real(2.)
+}
+
+/// Easing used to interpolate the banking angle between two controls
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    #[default]
+    SmoothStep,
+    SmootherStep,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Ease `i` from [0..1] to [0..1]
+    pub fn apply<T: RealField>(&self, i: T) -> T {
+        match self {
+            Easing::Linear => i,
+            Easing::SmoothStep => smooth_step(i),
+            Easing::SmootherStep => smoother_step(i),
+            Easing::EaseInOut => ease_in_out(i),
+        }
+    }
+}
+
+/// Sample between two track controls
+pub fn sample<T: RealField>(
+    begin: &TrackControl<T>,
+    end: &TrackControl<T>,
+    i: T,
+) -> TrackSample<T> {
+    sample_eased(begin, end, i, Easing::default())
+}
+
+/// Sample between two track controls, easing the banking angle with `easing`
+pub fn sample_eased<T: RealField>(
+    begin: &TrackControl<T>,
+    end: &TrackControl<T>,
+    i: T,
+    easing: Easing,
+) -> TrackSample<T> {
+    TrackSample {
+        position: spline(begin, end, i),
+        derivative: spline_deriv(begin, end, i),
+        angle: bank_angle(begin, end, i, easing),
+        index: i,
+        color: color(begin, end, i, easing),
+    }
+}
+
+/// Banking angle between two track controls
+pub(crate) fn bank_angle<T: RealField>(
+    begin: &TrackControl<T>,
+    end: &TrackControl<T>,
+    i: T,
+    easing: Easing,
+) -> T {
+    let end_angle = match end.full_roll {
+        true => end.angle,
+        false => shortest_arc(begin.angle, end.angle),
+    };
+    lerp(begin.angle, end_angle, easing.apply(i))
+}
+
+/// Color between two track controls
+pub(crate) fn color<T: RealField>(
+    begin: &TrackControl<T>,
+    end: &TrackControl<T>,
+    i: T,
+    easing: Easing,
+) -> [T; 3] {
+    let i = easing.apply(i);
+    [0, 1, 2].map(|c| lerp(begin.color[c], end.color[c], i))
+}
+
+/// Sample from a collection of track controls
+pub fn sample_collection<T: RealField>(
+    controls: &[TrackControl<T>],
+    i: T,
+) -> Option<TrackSample<T>> {
+    sample_collection_eased(controls, i, Easing::default())
+}
+
+/// Sample from a collection of track controls, easing the banking angle with `easing`
+pub fn sample_collection_eased<T: RealField>(
+    controls: &[TrackControl<T>],
+    i: T,
+    easing: Easing,
+) -> Option<TrackSample<T>> {
+    use core::convert::TryInto;
+    let whole = i.trunc();
+    let base: usize = (nalgebra::try_convert::<T, f64>(whole)? as i64).try_into().ok()?;
+    let begin = controls.get(base)?;
+    let end = controls.get(base+1)?;
+    let mut sample = sample_eased(begin, end, i - whole, easing);
+    sample.index = i;
+    Some(sample)
+}
+
+/// Derivative between two track controls
+pub fn spline_deriv<T: RealField>(
+    begin: &TrackControl<T>,
+    end: &TrackControl<T>,
+    i: T,
+) -> Vector3<T> {
+    let (one, three, six) = (T::one(), real::<T>(3.), real::<T>(6.));
+    let iv = one - i; // i inverse
+    let (w1, w2) = (begin.weight, end.weight);
+    let p0 = begin.position.coords;
+    let p1 = begin.front_ctrlp().coords * w1;
+    let p2 = end.back_ctrlp().coords * w2;
+    let p3 = end.position.coords;
+    let numerator = ((p1 - p0) * (three * iv * iv))
+        + ((p2 - p1) * (six * iv * i))
+        + ((p3 - p2) * (three * i * i));
+
+    // Quotient rule, for rational segments
+    let weight = iv * iv * iv + three * iv * iv * i * w1 + three * iv * i * i * w2 + i * i * i;
+    let weight_deriv =
+        three * iv * iv * (w1 - one) + six * iv * i * (w2 - w1) + three * i * i * (one - w2);
+    (numerator - spline(begin, end, i).coords * weight_deriv) / weight
+}
+
+/// Polynomial coefficients of the spline between two track controls, for evaluating the same
+/// segment many times
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentEval<T: RealField = f32> {
+    a: Vector3<T>,
+    b: Vector3<T>,
+    c: Vector3<T>,
+    d: Vector3<T>,
+    /// Coefficients of the denominator, `[a, b, c, d]`, which is 1 unless the segment is rational
+    weight: [T; 4],
+}
+
+impl<T: RealField> SegmentEval<T> {
+    /// Precompute the coefficients between `begin` and `end`
+    pub fn new(begin: &TrackControl<T>, end: &TrackControl<T>) -> Self {
+        let (one, two, three) = (T::one(), real::<T>(2.), real::<T>(3.));
+        let (w1, w2) = (begin.weight, end.weight);
+        let p0 = begin.position.coords;
+        let p1 = begin.front_ctrlp().coords * w1;
+        let p2 = end.back_ctrlp().coords * w2;
+        let p3 = end.position.coords;
+        Self {
+            a: p3 - p0 + (p1 - p2) * three,
+            b: (p0 - p1 * two + p2) * three,
+            c: (p1 - p0) * three,
+            d: p0,
+            weight: [(w1 - w2) * three, (one - w1 * two + w2) * three, (w1 - one) * three, one],
+        }
+    }
+
+    /// Numerator of the position and its first two derivatives at `i`
+    fn numerator(&self, i: T) -> [Vector3<T>; 3] {
+        let (two, three, six) = (real::<T>(2.), real::<T>(3.), real::<T>(6.));
+        [
+            ((self.a * i + self.b) * i + self.c) * i + self.d,
+            (self.a * (three * i) + self.b * two) * i + self.c,
+            self.a * (six * i) + self.b * two,
+        ]
+    }
+
+    /// Denominator and its first two derivatives at `i`
+    fn denominator(&self, i: T) -> [T; 3] {
+        let (two, three, six) = (real::<T>(2.), real::<T>(3.), real::<T>(6.));
+        let [a, b, c, d] = self.weight;
+        [
+            ((a * i + b) * i + c) * i + d,
+            (a * three * i + b * two) * i + c,
+            a * six * i + b * two,
+        ]
+    }
+
+    /// Position at `i`, equivalent to `spline`
+    pub fn eval(&self, i: T) -> Point3<T> {
+        let [n, ..] = self.numerator(i);
+        let [w, ..] = self.denominator(i);
+        Point3 { coords: n / w }
+    }
+
+    /// Derivative at `i`, equivalent to `spline_deriv`
+    pub fn eval_deriv(&self, i: T) -> Vector3<T> {
+        let [n, n1, _] = self.numerator(i);
+        let [w, w1, _] = self.denominator(i);
+        (n1 - n * (w1 / w)) / w
+    }
+
+    /// Second derivative at `i`
+    pub fn eval_deriv2(&self, i: T) -> Vector3<T> {
+        let [n, n1, n2] = self.numerator(i);
+        let [w, w1, w2] = self.denominator(i);
+        let position = n / w;
+        let deriv = (n1 - position * w1) / w;
+        (n2 - deriv * (real::<T>(2.) * w1) - position * w2) / w
+    }
+
+    /// Vector pointing toward the center of curvature at `i`, with the curvature as its length
+    pub fn curvature_vector(&self, i: T) -> Vector3<T> {
+        let deriv = self.eval_deriv(i);
+        let deriv2 = self.eval_deriv2(i);
+
+        // Component of the acceleration perpendicular to the track, per unit length squared
+        let speed_sq = deriv.magnitude_squared();
+        match speed_sq > T::default_epsilon() {
+            true => (deriv2 - deriv * (deriv.dot(&deriv2) / speed_sq)) / speed_sq,
+            false => Vector3::zeros(),
+        }
+    }
+
+    /// Curvature (inverse of the turning radius) at `i`
+    pub fn curvature(&self, i: T) -> T {
+        let deriv = self.eval_deriv(i);
+        let speed = deriv.magnitude();
+        match speed > T::default_epsilon() {
+            true => deriv.cross(&self.eval_deriv2(i)).magnitude() / speed.powi(3),
+            false => T::zero(),
+        }
+    }
+}
+
+/// Curvature (inverse of the turning radius) between two track controls
+pub fn curvature<T: RealField>(begin: &TrackControl<T>, end: &TrackControl<T>, i: T) -> T {
+    SegmentEval::new(begin, end).curvature(i)
+}
+
+/// Position between two track controls
+pub fn spline<T: RealField>(
+    begin: &TrackControl<T>,
+    end: &TrackControl<T>,
+    i: T,
+) -> Point3<T> {
+    let three = real::<T>(3.);
+    let iv = T::one() - i; // i inverse
+    let (w1, w2) = (begin.weight, end.weight);
+    let p0 = begin.position.coords;
+    let p1 = begin.front_ctrlp().coords * w1;
+    let p2 = end.back_ctrlp().coords * w2;
+    let p3 = end.position.coords;
+    let coords = (p0 * (iv * iv * iv))
+        + (p1 * (three * iv * iv * i))
+        + (p2 * (three * iv * i * i))
+        + (p3 * (i * i * i));
+    let weight = iv * iv * iv + three * iv * iv * i * w1 + three * iv * i * i * w2 + i * i * i;
+    Point3 {
+        coords: coords / weight,
+    }
+}
+
+/// The angle equivalent to `to` that is within half a turn of `from`
+pub(crate) fn shortest_arc<T: RealField>(from: T, to: T) -> T {
+    let diff = to - from;
+    if diff.abs() <= T::pi() {
+        to
+    } else {
+        from + wrap_angle(diff)
+    }
+}
+
+/// Wrap an angle into [-pi, pi)
+pub(crate) fn wrap_angle<T: RealField>(angle: T) -> T {
+    let turns = ((angle + T::pi()) / T::two_pi()).floor();
+    angle - turns * T::two_pi()
+}
+
+pub(crate) fn lerp<T: RealField>(a: T, b: T, i: T) -> T {
+    a * (T::one() - i) + b * i
+}
+
+/// Scalar constant in any precision
+fn real<T: RealField>(value: f64) -> T {
+    nalgebra::convert(value)
+}
@@ -0,0 +1,93 @@
+use crate::curve::{TrackControl, TrackSample};
+use glam::{Quat, Vec3};
+use nalgebra::{Point3, Quaternion, Unit, UnitQuaternion, Vector3};
+
+/// Conversion of nalgebra values into their glam equivalent
+pub trait ToGlam {
+    type Output;
+    fn to_glam(&self) -> Self::Output;
+}
+
+impl ToGlam for Vector3<f32> {
+    type Output = Vec3;
+    fn to_glam(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+impl ToGlam for Point3<f32> {
+    type Output = Vec3;
+    fn to_glam(&self) -> Vec3 {
+        self.coords.to_glam()
+    }
+}
+
+impl ToGlam for UnitQuaternion<f32> {
+    type Output = Quat;
+    fn to_glam(&self) -> Quat {
+        Quat::from_xyzw(self.i, self.j, self.k, self.w)
+    }
+}
+
+/// Conversion of glam values into their nalgebra equivalent
+pub trait FromGlam<G> {
+    fn from_glam(value: G) -> Self;
+}
+
+impl FromGlam<Vec3> for Vector3<f32> {
+    fn from_glam(value: Vec3) -> Self {
+        Vector3::new(value.x, value.y, value.z)
+    }
+}
+
+impl FromGlam<Vec3> for Point3<f32> {
+    fn from_glam(value: Vec3) -> Self {
+        Point3::new(value.x, value.y, value.z)
+    }
+}
+
+impl FromGlam<Quat> for UnitQuaternion<f32> {
+    /// Renormalizes, since glam doesn't guarantee a unit quaternion
+    fn from_glam(value: Quat) -> Self {
+        let [x, y, z, w] = value.to_array();
+        Unit::new_normalize(Quaternion::new(w, x, y, z))
+    }
+}
+
+impl TrackControl {
+    /// Create a new TrackControl from glam vectors
+    pub fn from_glam(position: Vec3, direction: Vec3, angle: f32) -> Self {
+        Self::new(Point3::from_glam(position), Vector3::from_glam(direction), angle)
+    }
+
+    pub fn position_glam(&self) -> Vec3 {
+        self.position.to_glam()
+    }
+
+    pub fn direction_glam(&self) -> Vec3 {
+        self.direction.to_glam()
+    }
+
+    pub fn front_ctrlp_glam(&self) -> Vec3 {
+        self.front_ctrlp().to_glam()
+    }
+
+    pub fn back_ctrlp_glam(&self) -> Vec3 {
+        self.back_ctrlp().to_glam()
+    }
+}
+
+impl TrackSample {
+    pub fn position_glam(&self) -> Vec3 {
+        self.position.to_glam()
+    }
+
+    pub fn derivative_glam(&self) -> Vec3 {
+        self.derivative.to_glam()
+    }
+
+    /// Orientation taking `axis` onto the track's direction, banked with the track
+    pub fn quaternion_glam(&self, axis: Vec3) -> Quat {
+        self.quaternion(&Vector3::from_glam(axis)).to_glam()
+    }
+}
@@ -1,2 +1,31 @@
+//! Bezier track construction, sampling and meshing.
+//!
+//! | Feature     | Default | Enables                                                    |
+//! |-------------|---------|------------------------------------------------------------|
+//! | `std`       | yes     | `controls`, `camera`, `cart` and file IO; else `no_std`    |
+//! | `render`    | yes     | klystron vertex conversion and the viewer binary           |
+//! | `gamepad`   | yes     | `controls::GamepadAxes` and the `Buttons` trait, via gilrs |
+//! | `wiiboard`  | yes     | `controls::WiiBoard` and controls for `WiiBoardRealtime`   |
+//! | `glam`      | no      | `interop`, conversions and accessors for glam types        |
+//! | `rayon`     | no      | `mesh::track_tess_path_par`                                |
+//! | `osc`       | no      | `controls::OscControls`                                    |
+//! | `websocket` | no      | `controls::WebSocketControls`                              |
+//!
+//! `curve`, `track` and `mesh` build with `--no-default-features`, producing plain vertex and
+//! index arrays. A headless build that also has file IO and the keyboard, mouse, UDP and scripted
+//! control sources is `--no-default-features --features std`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod curve;
+#[cfg(feature = "glam")]
+pub mod interop;
 pub mod track;
+#[cfg(feature = "std")]
 pub mod controls;
+pub mod mesh;
+#[cfg(feature = "std")]
+pub mod camera;
+#[cfg(feature = "std")]
+pub mod cart;
@@ -1,26 +1,72 @@
-use anyhow::{Result, format_err};
+use anyhow::{Context, Result, format_err};
 use klystron::{
     runtime_3d::{launch, App},
     DrawType, Engine, FramePacket, Object, Vertex, UNLIT_FRAG, UNLIT_VERT,
 };
 use nalgebra::{Matrix4, Point3, Vector3};
 use structopt::StructOpt;
-use twisty_beziers::controls::{self, GamepadAxes, TwoAxisControls};
-use twisty_beziers::track::{self, TrackControl, TrackFollower, TrackSample};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use twisty_beziers::camera::{self, ChaseCamera};
+use twisty_beziers::mesh;
+use twisty_beziers::cart::{Cart, EndMode, ReplayCart};
+#[cfg(feature = "gamepad")]
+use twisty_beziers::controls::GamepadAxes;
+use twisty_beziers::controls::{
+    self, AxisDeadzone, Recorder, Replay, TwoAxisControls, UdpControls,
+};
+use twisty_beziers::track::{self, Track, TrackControl};
+#[cfg(feature = "wiiboard")]
 use wiiboard::WiiBoardRealtime;
 
 struct MyApp {
     grid: Object,
     cart: Object,
     path: Object,
-    ctrlps: Vec<TrackControl>,
-    time: f32,
-    controls: Box<dyn TwoAxisControls>,
-    x_position: f32,
+    track: Track,
+    /// The first cart is the player's
+    carts: Vec<Cart>,
+    ghost: Option<(Object, ReplayCart)>,
+    last_frame: Instant,
+    /// The chase camera's spring state
+    chase: ChaseCamera,
+    controls: PlayerInput,
     opt: Opt,
 }
 
-const X_SENSITIVITY: f32 = 0.05;
+type PlayerControls = AxisDeadzone<Box<dyn TwoAxisControls>>;
+
+/// The player's input, which is only buffered for saving when --record is given
+enum PlayerInput {
+    Live(PlayerControls),
+    Recorded(Recorder<PlayerControls>),
+}
+
+impl PlayerInput {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        match self {
+            PlayerInput::Live(controls) => controls.axes(),
+            PlayerInput::Recorded(recorder) => recorder.axes(),
+        }
+    }
+}
+
+/// Distance traveled by each cart per second
+const CART_SPEED: f32 = 5.;
+
+/// Distance between the carts of a train
+const CART_SPACING: f32 = 7.;
+
+/// Height of the chase camera above the track
+const CHASE_HEIGHT: f32 = 3.;
+
+/// How tightly the chase camera follows
+const CHASE_STIFFNESS: f32 = 4.;
+
+/// How long UDP input holds its last value before reading zero
+const UDP_TIMEOUT: Duration = Duration::from_millis(500);
 
 impl App for MyApp {
     const NAME: &'static str = "MyApp";
@@ -29,9 +75,17 @@ impl App for MyApp {
 
     fn new(engine: &mut dyn Engine, opt: Self::Args) -> Result<Self> {
         let controls: Box<dyn TwoAxisControls> = match opt.input {
+            #[cfg(feature = "wiiboard")]
             InputDevice::WiiBoard => Box::new(WiiBoardRealtime::new(5, 5)),
+            #[cfg(feature = "gamepad")]
             InputDevice::GamePad => Box::new(GamepadAxes::new()?),
             InputDevice::Dummy => Box::new(controls::Dummy),
+            InputDevice::Udp(port) => Box::new(UdpControls::new(("0.0.0.0", port), UDP_TIMEOUT)?),
+        };
+        let controls = AxisDeadzone::new(controls, opt.threshold, 0.);
+        let controls = match opt.record {
+            Some(_) => PlayerInput::Recorded(Recorder::new(controls)),
+            None => PlayerInput::Live(controls),
         };
 
         let lines = engine.add_material(UNLIT_VERT, UNLIT_FRAG, DrawType::Lines)?;
@@ -47,26 +101,7 @@ impl App for MyApp {
         };
 
         // Track
-        let ctrlps = vec![
-            TrackControl::new(Point3::new(0., 0., 0.), Vector3::new(20., 0., 0.), 0.),
-            TrackControl::new(Point3::new(40., 20., 0.), Vector3::new(20., 0., 0.), 0.),
-            TrackControl::new(
-                Point3::new(80., 40., 0.),
-                Vector3::new(20., 0., 0.),
-                std::f32::consts::PI,
-            ),
-            TrackControl::new(
-                Point3::new(140., 00., 0.),
-                Vector3::new(0., -20., 0.),
-                std::f32::consts::PI,
-            ),
-            TrackControl::new(
-                Point3::new(00., -40., 0.),
-                Vector3::new(-20., 0., 0.),
-                std::f32::consts::PI,
-            ),
-            TrackControl::new(Point3::new(0., 0., 0.), Vector3::new(20., 0., 0.), 0.),
-        ];
+        let ctrlps = demo_track();
 
         // Cart
         let triangles = engine.add_material(UNLIT_VERT, UNLIT_FRAG, DrawType::Triangles)?;
@@ -86,9 +121,14 @@ impl App for MyApp {
         )?;
 
         // Path
-        let (vertices, mut indices) = track_tess_path(&ctrlps, 8, 8.0, 0.5);
+        #[cfg(not(feature = "rayon"))]
+        let (vertices, mut indices) =
+            mesh::track_tess_path(&ctrlps, opt.lanes, opt.width, opt.resolution, None);
+        #[cfg(feature = "rayon")]
+        let (vertices, mut indices) =
+            mesh::track_tess_path_par(&ctrlps, opt.lanes, opt.width, opt.resolution, None);
         double_side(&mut indices);
-        let mesh = engine.add_mesh(&vertices, &indices)?;
+        let mesh = engine.add_mesh(&mesh::to_render(&vertices), &indices)?;
 
         let path = Object {
             mesh,
@@ -96,65 +136,125 @@ impl App for MyApp {
             transform: Matrix4::identity(),
         };
 
+        let end_mode = opt.endmode.unwrap_or(match track::is_closed(&ctrlps) {
+            true => EndMode::Loop,
+            false => EndMode::Stop,
+        });
+
+        // Ghost cart, re-running a recorded session
+        let ghost = match &opt.ghost {
+            Some(path) => {
+                let (mut vertices, indices) = carpet(2., 3.);
+                for vertex in &mut vertices {
+                    vertex.color = [0.5; 3];
+                }
+                let ghost = Object {
+                    mesh: engine.add_mesh(&vertices, &indices)?,
+                    ..cart
+                };
+                let log = Replay::load(path)?.log().to_vec();
+                let mut cart = Cart::new(0., CART_SPEED);
+                cart.end_mode = end_mode;
+                Some((ghost, ReplayCart::new(cart, log, opt.sensitivity)))
+            }
+            None => None,
+        };
+
+        // Carts, with the player's in front
+        let count = opt.carts.max(1);
+        let carts = (0..count)
+            .map(|idx| {
+                let mut cart = Cart::new((count - 1 - idx) as f32 * CART_SPACING, CART_SPEED);
+                cart.end_mode = end_mode;
+                cart
+            })
+            .collect();
+
         Ok(Self {
             opt,
-            x_position: 0.,
             controls,
             path,
-            ctrlps,
+            track: Track::new(ctrlps),
+            carts,
+            ghost,
+            last_frame: Instant::now(),
+            chase: ChaseCamera::default(),
             cart,
             grid,
-            time: 0.0,
         })
     }
 
     fn next_frame(&mut self, engine: &mut dyn Engine) -> Result<FramePacket> {
-        // Sample track position
-        let sample = match track::sample_collection(&self.ctrlps, self.time) {
-            Some(s) => s,
-            None => {
-                self.time = 0.;
-                track::sample_collection(&self.ctrlps, self.time).unwrap()
-            }
-        };
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f32();
+        self.last_frame = now;
 
-        // Update X position based on game input
+        // Update the player's X position based on game input
         let (x, _y) = self.controls.axes().expect("Input device error");
-        const DEADZONE: f32 = 0.2;
-        if x.abs() > DEADZONE {
-            let x = if x > 0. { x - DEADZONE } else { x + DEADZONE } / (1. - DEADZONE);
-            self.x_position += X_SENSITIVITY * x;
+        self.carts[0].steer(x, self.opt.sensitivity);
+
+        // Move the carts
+        let mut objects = vec![self.grid, self.path];
+        for cart in &mut self.carts {
+            cart.advance(&self.track, dt);
+            if let Some(transform) = cart.transform(&self.track) {
+                objects.push(Object {
+                    transform,
+                    ..self.cart
+                });
+            }
         }
 
-        // Update time (It's actually an index into the spline set but shhh)
-        self.time += 0.08 / sample.derivative.magnitude();
-        engine.update_time_value(self.time)?;
+        // Move the ghost
+        if let Some((object, ghost)) = &mut self.ghost {
+            ghost.update(&self.track, dt);
+            if let Some(transform) = ghost.cart.transform(&self.track) {
+                objects.push(Object {
+                    transform,
+                    ..*object
+                });
+            }
+        }
 
-        // Determine transform for world
-        let quat = sample.quaternion(&Vector3::x_axis());
-        let cart_position = sample.position + road_norm(&sample) * self.x_position;
-        let base_transform =
-            Matrix4::new_translation(&cart_position.coords) * quat.to_homogeneous();
+        // Time is the index into the spline set under the player
+        let player = self.carts[0];
+        let sample = player.sample(&self.track).unwrap();
+        engine.update_time_value(sample.index)?;
 
-        // Move the cart
-        self.cart.transform = base_transform;
+        // Trail the player
+        if let Some(trail) = self.opt.chase {
+            let mut distance = player.distance - trail;
+            if self.track.is_closed() {
+                distance = distance.rem_euclid(self.track.length());
+            }
+            if let Some(mut target) = self.track.sample_at_distance(distance) {
+                target.position += target.up() * CHASE_HEIGHT;
+                self.chase.update(&target, CHASE_STIFFNESS, dt);
+            }
+        }
 
         // Move the whole scene
-        let base_transform = match self.opt.motion {
-            false => Matrix4::identity(),
-            true => base_transform.try_inverse().unwrap(),
+        let base_transform = match (self.opt.chase, self.opt.motion) {
+            (Some(_), _) => self.chase.transform().try_inverse().unwrap(),
+            (None, true) => camera::ride_camera(&sample, Vector3::new(0., 0., player.lateral)),
+            (None, false) => Matrix4::identity(),
         };
 
         Ok(FramePacket {
             base_transform,
-            objects: vec![self.grid, self.cart, self.path],
+            objects,
         })
     }
 }
 
-pub fn road_norm(sample: &TrackSample) -> Vector3<f32> {
-    let quat = sample.quaternion(&Vector3::x_axis());
-    quat.transform_vector(&Vector3::z_axis())
+impl Drop for MyApp {
+    fn drop(&mut self) {
+        if let (Some(path), PlayerInput::Recorded(recorder)) = (&self.opt.record, &self.controls) {
+            if let Err(e) = recorder.save(path) {
+                eprintln!("Failed to save recording: {:#}", e);
+            }
+        }
+    }
 }
 
 pub fn double_side(indices: &mut Vec<u16>) {
@@ -165,84 +265,15 @@ pub fn double_side(indices: &mut Vec<u16>) {
     }
 }
 
-pub fn track_tess_path(
-    segments: &[TrackControl],
-    lanes: i32,
-    width: f32,
-    resolution: f32,
-) -> (Vec<Vertex>, Vec<u16>) {
-    let mut vertices = Vec::new();
-    let max_idx = segments.len() as f32;
-    let mut follower = TrackFollower::new(segments, resolution);
-
-    // Generate vertices
-    let total_lanes = lanes * 2 + 1;
-    let mut total_rows = 0;
-    while let Some(sample) = follower.next() {
-        let normal = road_norm(&sample) * width;
-        let v = sample.index / max_idx;
-        let w = follower.i;
-        for lane in -lanes..=lanes {
-            let u = lane as f32 / total_lanes as f32;
-            let pos = sample.position + normal * (u * width);
-            vertices.push(Vertex::new(*pos.coords.as_ref(), [(u + 0.5) / 2., v, w]));
-        }
-        total_rows += 1;
-    }
-
-    // Tesselate indices
-    let mut indices = Vec::new();
-    for row in 0..total_rows - 1 {
-        for col in 0..total_lanes - 1 {
-            let idx = (row * total_lanes + col) as u16;
-            let total_lanes = total_lanes as u16;
-
-            indices.push(idx);
-            indices.push(idx + 1);
-            indices.push(idx + total_lanes);
-
-            indices.push(idx + total_lanes + 1);
-            indices.push(idx + total_lanes);
-            indices.push(idx + 1);
-        }
-    }
-
-    (vertices, indices)
-}
-
-pub fn track_center_line(
-    segments: &[TrackControl],
-    resolution: f32,
-    color: [f32; 3],
-) -> (Vec<Vertex>, Vec<u16>) {
-    let vertices: Vec<Vertex> = TrackFollower::new(segments, resolution)
-        .map(|s| Vertex::new(*s.position.coords.as_ref(), color))
-        .collect();
-    let indices = (1..vertices.len() as u16 * 2).map(|i| i / 2).collect();
-    (vertices, indices)
-}
-
-pub fn track_trace_away(
-    segments: &[TrackControl],
-    resolution: f32,
-    away: f32,
-    axis: Vector3<f32>,
-    color: [f32; 3],
-    vertices: &mut Vec<Vertex>,
-) {
-    for s in TrackFollower::new(segments, resolution) {
-        vertices.push(Vertex::new(*s.position.coords.as_ref(), color));
-        let quat = s.quaternion(&Vector3::y_axis());
-        let v = s.position + quat.transform_vector(&axis) * away;
-        vertices.push(Vertex::new(*v.coords.as_ref(), color));
-    }
-}
-
 #[derive(Debug)]
 enum InputDevice {
     Dummy,
+    #[cfg(feature = "wiiboard")]
     WiiBoard,
+    #[cfg(feature = "gamepad")]
     GamePad,
+    /// UDP on the given port
+    Udp(u16),
 }
 
 use std::str::FromStr;
@@ -252,9 +283,17 @@ impl FromStr for InputDevice {
     fn from_str(s: &str) -> Result<Self> {
         match s {
             "dummy" => Ok(Self::Dummy),
+            #[cfg(feature = "wiiboard")]
             "wii" => Ok(Self::WiiBoard),
+            #[cfg(feature = "gamepad")]
             "pad" => Ok(Self::GamePad),
-            _ => Err(format_err!("Unrecognized input device {}", s)),
+            "udp" => Ok(Self::Udp(controls::UDP_DEFAULT_PORT)),
+            _ => match s.strip_prefix("udp:") {
+                Some(port) => Ok(Self::Udp(
+                    port.parse::<u16>().with_context(|| format!("Invalid UDP port {}", port))?,
+                )),
+                None => Err(format_err!("Unrecognized input device {}", s)),
+            },
         }
     }
 }
@@ -266,20 +305,106 @@ struct Opt {
     #[structopt(short, long)]
     vr: bool,
 
-    /// Wii balance board mode
+    /// Input device; dummy, wii, pad, or udp[:PORT] for two little-endian f32s per datagram
     #[structopt(short, long, default_value = "dummy")]
     input: InputDevice,
 
     /// Attach yourself to the game and go weeeeee
     #[structopt(short, long)]
     motion: bool,
+
+    /// Sideways movement per frame at full input
+    #[structopt(long, default_value = "0.05")]
+    sensitivity: f32,
+
+    /// Sideways input at or below this magnitude is ignored, so an idle stick doesn't drift
+    #[structopt(long, default_value = "0.2")]
+    threshold: f32,
+
+    /// Lanes to either side of the track's center line
+    #[structopt(long, default_value = "8")]
+    lanes: i32,
+
+    /// Width of the track
+    #[structopt(long, default_value = "8.0")]
+    width: f32,
+
+    /// Distance between rows of the track mesh
+    #[structopt(long, default_value = "0.5")]
+    resolution: f32,
+
+    /// Number of carts in the train
+    #[structopt(long, default_value = "1")]
+    carts: usize,
+
+    /// Race a ghost cart replaying a session recorded with --record
+    #[structopt(long, parse(from_os_str))]
+    ghost: Option<PathBuf>,
+
+    /// Record this session's input to a CSV file on exit
+    #[structopt(long, parse(from_os_str))]
+    record: Option<PathBuf>,
+
+    /// Follow the player with a smoothed camera this far behind along the track
+    #[structopt(long)]
+    chase: Option<f32>,
+
+    /// What carts do at the end of the track; loop, stop or reverse. Defaults to loop on closed
+    /// tracks and stop otherwise
+    #[structopt(long)]
+    endmode: Option<EndMode>,
+
+    /// Write the track's mesh to an OBJ file and exit, without opening a window
+    #[structopt(long, parse(from_os_str))]
+    export: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     let opt = Opt::from_args();
+    if let Some(path) = &opt.export {
+        return export(path, &opt);
+    }
     launch::<MyApp>(opt.vr, opt)
 }
 
+/// Control points of the demo's track
+fn demo_track() -> Vec<TrackControl> {
+    vec![
+        TrackControl::new(Point3::new(0., 0., 0.), Vector3::new(20., 0., 0.), 0.),
+        TrackControl::new(Point3::new(40., 20., 0.), Vector3::new(20., 0., 0.), 0.),
+        TrackControl::new(
+            Point3::new(80., 40., 0.),
+            Vector3::new(20., 0., 0.),
+            std::f32::consts::PI,
+        ),
+        TrackControl::new(
+            Point3::new(140., 00., 0.),
+            Vector3::new(0., -20., 0.),
+            std::f32::consts::PI,
+        ),
+        TrackControl::new(
+            Point3::new(00., -40., 0.),
+            Vector3::new(-20., 0., 0.),
+            std::f32::consts::PI,
+        ),
+        TrackControl::new(Point3::new(0., 0., 0.), Vector3::new(20., 0., 0.), 0.),
+    ]
+}
+
+/// Tesselate the demo's track and write it to an OBJ file with its texture coordinates, without
+/// starting the engine
+fn export(path: &Path, opt: &Opt) -> Result<()> {
+    let (vertices, indices) =
+        mesh::track_tess_path(&demo_track(), opt.lanes, opt.width, opt.resolution, None);
+    let indices: Vec<u32> = indices.into_iter().map(u32::from).collect();
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    mesh::write_obj(&vertices, &indices, true, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
 fn grid(size: i32, scale: f32, color: [f32; 3]) -> (Vec<Vertex>, Vec<u16>) {
     let mut vertices = Vec::new();
     let mut indices = Vec::new();
@@ -0,0 +1,599 @@
+use crate::track::{is_closed, TrackControl, TrackFollower, TrackSample};
+use core::convert::TryFrom;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use anyhow::Result;
+use nalgebra::{Point3, Vector3};
+#[cfg(feature = "std")]
+use std::io::Write;
+
+/// Plain mesh vertex; the color slot may instead carry texture coordinates, depending on the mesh
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vertex {
+    pub pos: [f32; 3],
+    pub color: [f32; 3],
+    /// Unit surface normal, or zero for meshes without normals
+    pub normal: [f32; 3],
+}
+
+impl Vertex {
+    pub fn new(pos: [f32; 3], color: [f32; 3]) -> Self {
+        Self {
+            pos,
+            color,
+            normal: [0.; 3],
+        }
+    }
+
+    pub fn with_normal(mut self, normal: [f32; 3]) -> Self {
+        self.normal = normal;
+        self
+    }
+}
+
+/// Convert a vertex index for a `u32` index buffer, panicking if the mesh has outgrown it
+fn index(idx: usize) -> u32 {
+    u32::try_from(idx).expect("Mesh has too many vertices for u32 indices")
+}
+
+#[cfg(feature = "render")]
+impl From<Vertex> for klystron::Vertex {
+    fn from(vertex: Vertex) -> Self {
+        klystron::Vertex::new(vertex.pos, vertex.color)
+    }
+}
+
+/// Convert vertices into klystron's format for upload with `add_mesh`
+#[cfg(feature = "render")]
+pub fn to_render(vertices: &[Vertex]) -> Vec<klystron::Vertex> {
+    vertices.iter().copied().map(Into::into).collect()
+}
+
+/// Walls raised `height` along the track's up vector, `width` to either side of the center line.
+/// The walls bank with the track, and both their front faces (counter-clockwise) and their vertex
+/// normals point inward.
+pub fn walls(
+    controls: &[TrackControl],
+    width: f32,
+    height: f32,
+    resolution: f32,
+    left: bool,
+    right: bool,
+    color: [f32; 3],
+) -> (Vec<Vertex>, Vec<u32>) {
+    let mut sides = Vec::new();
+    if left {
+        sides.push(-1.);
+    }
+    if right {
+        sides.push(1.);
+    }
+
+    // Generate vertices; a bottom and top vertex per side per row
+    let mut vertices = Vec::new();
+    let mut total_rows = 0;
+    for sample in TrackFollower::new(controls, resolution) {
+        let (lateral, up) = (sample.right(), sample.up());
+        for side in &sides {
+            let bottom = sample.position + lateral * (width * side);
+            let top = bottom + up * height;
+            let inward = *(-lateral * *side).as_ref();
+            vertices.push(Vertex::new(*bottom.coords.as_ref(), color).with_normal(inward));
+            vertices.push(Vertex::new(*top.coords.as_ref(), color).with_normal(inward));
+        }
+        total_rows += 1;
+    }
+
+    // Tesselate indices, winding each side so that it faces the center line
+    let stride = sides.len() * 2;
+    let mut indices = Vec::new();
+    for row in 0..total_rows.max(1) - 1 {
+        for (i, side) in sides.iter().enumerate() {
+            let bottom = row * stride + i * 2;
+            let [bottom, top, next_bottom, next_top] =
+                [bottom, bottom + 1, bottom + stride, bottom + stride + 1].map(index);
+            if *side > 0. {
+                indices.extend_from_slice(&[bottom, top, next_bottom]);
+                indices.extend_from_slice(&[next_top, next_bottom, top]);
+            } else {
+                indices.extend_from_slice(&[bottom, next_bottom, top]);
+                indices.extend_from_slice(&[next_top, top, next_bottom]);
+            }
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Caps closing the channel between both `walls` at the start and end of an open track, so the
+/// ends can't be seen through. Each cap is a quad spanning the track's width and the walls' height
+/// at the walls' first or last row, facing outward along the tangent. Closed tracks have no ends,
+/// so they get no caps.
+pub fn wall_caps(
+    controls: &[TrackControl],
+    width: f32,
+    height: f32,
+    resolution: f32,
+    color: [f32; 3],
+) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    if is_closed(controls) {
+        return (vertices, indices);
+    }
+
+    let mut follower = TrackFollower::new(controls, resolution);
+    let first = follower.next();
+    let last = follower.last();
+    let ends = first.into_iter().map(|s| (s, -1.)).chain(last.map(|s| (s, 1.)));
+    for (sample, direction) in ends {
+        let (lateral, up) = (sample.right(), sample.up());
+        let outward = sample.derivative.normalize() * direction;
+        let base = vertices.len();
+        for corner in &[
+            sample.position - lateral * width,
+            sample.position + lateral * width,
+            sample.position + lateral * width + up * height,
+            sample.position - lateral * width + up * height,
+        ] {
+            let vertex = Vertex::new(*corner.coords.as_ref(), color);
+            vertices.push(vertex.with_normal(*outward.as_ref()));
+        }
+
+        // Left to right then up winds toward the back, so the end cap is wound the other way
+        let [bl, br, tr, tl] = [base, base + 1, base + 2, base + 3].map(index);
+        if direction < 0. {
+            indices.extend_from_slice(&[bl, br, tr, tr, tl, bl]);
+        } else {
+            indices.extend_from_slice(&[bl, tr, br, tr, bl, tl]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Low-poly driving surface of a track, for physics
+pub struct CollisionStrip {
+    pub vertices: Vec<Point3<f32>>,
+    pub triangles: Vec<[u32; 3]>,
+    /// Upward surface normal of each quad; quad `n` is made of triangles `2n` and `2n + 1`
+    pub normals: Vec<Vector3<f32>>,
+}
+
+/// A single quad strip following the driving surface, `width` to either side of the center line.
+/// Rows are placed roughly `spacing` apart along the track, and there are no lanes.
+pub fn collision_strip(controls: &[TrackControl], width: f32, spacing: f32) -> CollisionStrip {
+    // Generate vertices; a left and right vertex per row
+    let mut vertices = Vec::new();
+    for sample in TrackFollower::new(controls, spacing) {
+        let lateral = sample.right();
+        vertices.push(sample.position - lateral * width);
+        vertices.push(sample.position + lateral * width);
+    }
+
+    // Tesselate quads, facing up
+    let mut triangles = Vec::new();
+    let mut normals = Vec::new();
+    let rows = vertices.chunks_exact(2);
+    for (idx, (current, next)) in rows.clone().zip(rows.skip(1)).enumerate() {
+        let left = idx as u32 * 2;
+        let right = left + 1;
+        let next_left = left + 2;
+        let next_right = left + 3;
+        triangles.push([left, right, next_left]);
+        triangles.push([next_right, next_left, right]);
+
+        let diagonal_a = next[1] - current[0];
+        let diagonal_b = next[0] - current[1];
+        normals.push(diagonal_a.cross(&diagonal_b).normalize());
+    }
+
+    CollisionStrip {
+        vertices,
+        triangles,
+        normals,
+    }
+}
+
+/// Concatenate meshes into a single vertex and index buffer. Panics if the result has more
+/// vertices than `u32` indices can address.
+pub fn merge(meshes: &[(Vec<Vertex>, Vec<u32>)]) -> (Vec<Vertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (mesh_vertices, mesh_indices) in meshes {
+        let offset = vertices.len();
+        vertices.extend_from_slice(mesh_vertices);
+        indices.extend(mesh_indices.iter().map(|&i| index(i as usize + offset)));
+    }
+    (vertices, indices)
+}
+
+/// Merge vertices whose positions lie within `epsilon` of each other, and remap `indices` to match.
+/// A welded vertex keeps the position and color of the first vertex merged into it, and the
+/// average of their normals, so that shading is smooth across shared edges. Triangles collapsed by
+/// welding are removed.
+pub fn weld(vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>, epsilon: f32) {
+    // Bucket welded vertices into a grid of `epsilon` sized cells, so that only neighboring cells
+    // need to be searched for a match
+    let cell_size = epsilon.max(f32::EPSILON);
+    let cell = |p: f32| (p / cell_size).floor() as i64;
+
+    let mut welded: Vec<Vertex> = Vec::new();
+    let mut normals: Vec<Vector3<f32>> = Vec::new();
+    let mut grid: BTreeMap<(i64, i64, i64), Vec<usize>> = BTreeMap::new();
+    let mut remap = Vec::with_capacity(vertices.len());
+    for vertex in vertices.iter() {
+        let [x, y, z] = vertex.pos;
+        let (x, y, z) = (cell(x), cell(y), cell(z));
+        let within = |idx: &&usize| {
+            let other = welded[**idx].pos;
+            let dist_sq: f32 = (0..3).map(|i| (other[i] - vertex.pos[i]).powi(2)).sum();
+            dist_sq <= epsilon * epsilon
+        };
+
+        let mut found = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(bucket) = grid.get(&(x + dx, y + dy, z + dz)) {
+                        if let Some(idx) = bucket.iter().find(within) {
+                            found = Some(*idx);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        let idx = match found {
+            Some(idx) => idx,
+            None => {
+                let idx = welded.len();
+                welded.push(*vertex);
+                normals.push(Vector3::zeros());
+                grid.entry((x, y, z)).or_default().push(idx);
+                idx
+            }
+        };
+        normals[idx] += Vector3::from(vertex.normal);
+        remap.push(index(idx));
+    }
+
+    // Vertices without normals sum to zero, and keep it
+    for (vertex, normal) in welded.iter_mut().zip(normals) {
+        vertex.normal = *normal.try_normalize(0.).unwrap_or(normal).as_ref();
+    }
+    *indices = indices
+        .chunks_exact(3)
+        .map(|tri| [tri[0], tri[1], tri[2]].map(|i| remap[i as usize]))
+        .filter(|[a, b, c]| a != b && b != c && c != a)
+        .flatten()
+        .collect();
+    *vertices = welded;
+}
+
+/// Write a triangle mesh as Wavefront OBJ. With `texture_coords`, each vertex's color slot is
+/// taken as the `(u, v, w)` the track's tessellators put there and written as `vt`; otherwise
+/// only positions are written, since OBJ has no vertex colors. Normals are written too, unless
+/// the mesh has none.
+#[cfg(feature = "std")]
+pub fn write_obj(
+    vertices: &[Vertex],
+    indices: &[u32],
+    texture_coords: bool,
+    mut writer: impl Write,
+) -> Result<()> {
+    let has_normals = vertices.iter().any(|v| v.normal != [0.; 3]);
+    for vertex in vertices {
+        let [x, y, z] = vertex.pos;
+        writeln!(writer, "v {} {} {}", x, y, z)?;
+        if texture_coords {
+            let [u, v, w] = vertex.color;
+            writeln!(writer, "vt {} {} {}", u, v, w)?;
+        }
+        if has_normals {
+            let [x, y, z] = vertex.normal;
+            writeln!(writer, "vn {} {} {}", x, y, z)?;
+        }
+    }
+    for face in indices.chunks_exact(3) {
+        // OBJ indices start at 1, and each vertex has the texture coordinate and normal of the
+        // same index
+        write!(writer, "f")?;
+        for &idx in face {
+            let idx = idx + 1;
+            match (texture_coords, has_normals) {
+                (true, true) => write!(writer, " {0}/{0}/{0}", idx)?,
+                (true, false) => write!(writer, " {0}/{0}", idx)?,
+                (false, true) => write!(writer, " {0}//{0}", idx)?,
+                (false, false) => write!(writer, " {}", idx)?,
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// The track's center line as a line list, with a segment between each consecutive pair of samples
+pub fn track_center_line(
+    segments: &[TrackControl],
+    resolution: f32,
+    color: [f32; 3],
+) -> (Vec<Vertex>, Vec<u16>) {
+    let vertices: Vec<Vertex> = TrackFollower::new(segments, resolution)
+        .map(|s| Vertex::new(*s.position.coords.as_ref(), color))
+        .collect();
+    let indices = (1..vertices.len() as u16).flat_map(|i| IntoIterator::into_iter([i - 1, i]));
+    (vertices, indices.collect())
+}
+
+/// A line from each sample along the track, e.g. for ladders, supports or debug normals. Samples
+/// are oriented by rotating `forward` onto the track's direction and banking with the track, and
+/// each line runs `away` units along `axis` in that frame. With `forward` as x, y is the track's
+/// up and z its right.
+pub fn trace_away(
+    segments: &[TrackControl],
+    resolution: f32,
+    away: f32,
+    axis: &Vector3<f32>,
+    forward: &Vector3<f32>,
+    color: [f32; 3],
+) -> (Vec<Vertex>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    for s in TrackFollower::new(segments, resolution) {
+        let quat = s.quaternion(forward);
+        let v = s.position + quat.transform_vector(axis) * away;
+        vertices.push(Vertex::new(*s.position.coords.as_ref(), color));
+        vertices.push(Vertex::new(*v.coords.as_ref(), color));
+    }
+    let indices = (0..vertices.len() as u16).collect();
+    (vertices, indices)
+}
+
+/// Tesselate the track surface into lanes. By default the color slot carries `(u, v, w)`
+/// texture coordinates for the floor shader; pass `lane_color` to color by lane and `v` instead.
+/// Panics if the surface is too long for `u16` indices, as `tess_indices` does.
+pub fn track_tess_path(
+    segments: &[TrackControl],
+    lanes: i32,
+    width: f32,
+    resolution: f32,
+    lane_color: Option<&dyn Fn(i32, f32) -> [f32; 3]>,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let vertices: Vec<Vertex> =
+        tess_vertices(segments, lanes, width, resolution, lane_color).collect();
+    let total_rows = vertices.len() / (lanes * 2 + 1) as usize;
+    (vertices, tess_indices(total_rows, lanes).collect())
+}
+
+/// Tesselate the track surface into lanes like `track_tess_path`, colored with the color
+/// interpolated between the track's controls
+pub fn track_tess_path_colored(
+    segments: &[TrackControl],
+    lanes: i32,
+    width: f32,
+    resolution: f32,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let vertices: Vec<Vertex> =
+        tess_vertices_with(segments, lanes, width, resolution, Coloring::Track).collect();
+    let total_rows = vertices.len() / (lanes * 2 + 1) as usize;
+    (vertices, tess_indices(total_rows, lanes).collect())
+}
+
+/// Vertices of `track_tess_path`, generated lazily row by row without allocating
+pub fn tess_vertices<'a>(
+    segments: &'a [TrackControl],
+    lanes: i32,
+    width: f32,
+    resolution: f32,
+    lane_color: Option<&'a dyn Fn(i32, f32) -> [f32; 3]>,
+) -> impl Iterator<Item = Vertex> + 'a {
+    tess_vertices_with(segments, lanes, width, resolution, lane_color.into())
+}
+
+fn tess_vertices_with<'a>(
+    segments: &'a [TrackControl],
+    lanes: i32,
+    width: f32,
+    resolution: f32,
+    coloring: Coloring<'a>,
+) -> impl Iterator<Item = Vertex> + 'a {
+    let max_idx = segments.len() as f32;
+    let mut follower = TrackFollower::new(segments, resolution);
+    core::iter::from_fn(move || follower.next().map(|sample| (sample, follower.i)))
+        .flat_map(move |(sample, w)| tess_path_row(sample, w, lanes, width, max_idx, coloring))
+}
+
+/// Number of rows `tess_vertices` produces, each of `lanes * 2 + 1` vertices
+pub fn tess_rows(segments: &[TrackControl], resolution: f32) -> usize {
+    TrackFollower::new(segments, resolution).count()
+}
+
+/// Indices of `track_tess_path` for a surface of `total_rows` rows, as triangles. Panics if the
+/// surface has more vertices than `u16` indices can address; `MeshChunker` splits long tracks into
+/// meshes that fit.
+pub fn tess_indices(total_rows: usize, lanes: i32) -> impl Iterator<Item = u16> {
+    let total_lanes = lanes * 2 + 1;
+    assert!(
+        total_rows * total_lanes as usize <= u16::MAX as usize + 1,
+        "Track surface has too many vertices for u16 indices"
+    );
+    (0..total_rows as i32 - 1).flat_map(move |row| {
+        (0..total_lanes - 1).flat_map(move |col| {
+            let idx = (row * total_lanes + col) as u16;
+            let total_lanes = total_lanes as u16;
+            IntoIterator::into_iter([
+                idx,
+                idx + 1,
+                idx + total_lanes,
+                idx + total_lanes + 1,
+                idx + total_lanes,
+                idx + 1,
+            ])
+        })
+    })
+}
+
+/// Tesselates the track surface like `track_tess_path`, as a series of self-contained meshes of
+/// at most `max_vertices` vertices each. Each chunk starts with the last row of the one before it,
+/// so that there is no gap between them.
+pub struct MeshChunker<'a> {
+    follower: TrackFollower<'a>,
+    lanes: i32,
+    width: f32,
+    max_idx: f32,
+    rows_per_chunk: usize,
+    coloring: Coloring<'a>,
+    /// Last row of the previous chunk, and the follower's index after it
+    carry: Option<(TrackSample, f32)>,
+}
+
+impl<'a> MeshChunker<'a> {
+    /// `max_vertices` is capped to what `u16` indices can address, and is at least two rows
+    pub fn new(
+        segments: &'a [TrackControl],
+        lanes: i32,
+        width: f32,
+        resolution: f32,
+        max_vertices: usize,
+    ) -> Self {
+        let row_len = (lanes * 2 + 1) as usize;
+        let max_vertices = max_vertices.min(u16::MAX as usize + 1);
+        Self {
+            follower: TrackFollower::new(segments, resolution),
+            lanes,
+            width,
+            max_idx: segments.len() as f32,
+            rows_per_chunk: (max_vertices / row_len).max(2),
+            coloring: Coloring::Uv,
+            carry: None,
+        }
+    }
+
+    /// Color by lane and `v`, as in `track_tess_path`
+    pub fn with_lane_color(mut self, lane_color: &'a dyn Fn(i32, f32) -> [f32; 3]) -> Self {
+        self.coloring = Coloring::Lane(lane_color);
+        self
+    }
+
+    /// Color with the color interpolated between the track's controls
+    pub fn with_track_color(mut self) -> Self {
+        self.coloring = Coloring::Track;
+        self
+    }
+
+    fn row(&self, sample: TrackSample, w: f32) -> impl Iterator<Item = Vertex> + 'a {
+        tess_path_row(sample, w, self.lanes, self.width, self.max_idx, self.coloring)
+    }
+}
+
+impl Iterator for MeshChunker<'_> {
+    type Item = (Vec<Vertex>, Vec<u16>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut vertices = Vec::new();
+        let mut total_rows = 0;
+        if let Some((sample, w)) = self.carry {
+            vertices.extend(self.row(sample, w));
+            total_rows += 1;
+        }
+
+        while total_rows < self.rows_per_chunk {
+            let sample = match self.follower.next() {
+                Some(sample) => sample,
+                None => break,
+            };
+            let w = self.follower.i;
+            vertices.extend(self.row(sample, w));
+            self.carry = Some((sample, w));
+            total_rows += 1;
+        }
+
+        // A lone row is either the end of the track or a track too short to have a surface
+        match total_rows < 2 {
+            true => None,
+            false => Some((vertices, tess_indices(total_rows, self.lanes).collect())),
+        }
+    }
+}
+
+/// Parallel version of `track_tess_path`, producing identical output. Samples are stepped along
+/// the track serially, then each row of vertices is generated in parallel.
+#[cfg(feature = "rayon")]
+pub fn track_tess_path_par(
+    segments: &[TrackControl],
+    lanes: i32,
+    width: f32,
+    resolution: f32,
+    lane_color: Option<&(dyn Fn(i32, f32) -> [f32; 3] + Sync)>,
+) -> (Vec<Vertex>, Vec<u16>) {
+    use rayon::prelude::*;
+    let max_idx = segments.len() as f32;
+    let mut follower = TrackFollower::new(segments, resolution);
+
+    // Step along the track, keeping the follower's index after each step
+    let mut samples = Vec::new();
+    while let Some(sample) = follower.next() {
+        samples.push((sample, follower.i));
+    }
+
+    // Generate vertices
+    let rows: Vec<Vec<Vertex>> = samples
+        .par_iter()
+        .map(|(sample, w)| {
+            let lane_color = lane_color.map(|f| f as &dyn Fn(i32, f32) -> [f32; 3]);
+            tess_path_row(*sample, *w, lanes, width, max_idx, lane_color.into()).collect()
+        })
+        .collect();
+    let vertices = rows.into_iter().flatten().collect();
+
+    (vertices, tess_indices(samples.len(), lanes).collect())
+}
+
+/// What the color slot of the track surface's vertices carries
+#[derive(Clone, Copy)]
+enum Coloring<'a> {
+    /// `(u, v, w)` texture coordinates for the floor shader
+    Uv,
+    /// Color by lane and `v`
+    Lane(&'a dyn Fn(i32, f32) -> [f32; 3]),
+    /// The color interpolated between the track's controls
+    Track,
+}
+
+impl<'a> From<Option<&'a dyn Fn(i32, f32) -> [f32; 3]>> for Coloring<'a> {
+    fn from(lane_color: Option<&'a dyn Fn(i32, f32) -> [f32; 3]>) -> Self {
+        match lane_color {
+            Some(lane_color) => Coloring::Lane(lane_color),
+            None => Coloring::Uv,
+        }
+    }
+}
+
+/// A row of vertices across the track surface for `track_tess_path`
+fn tess_path_row<'a>(
+    sample: TrackSample,
+    w: f32,
+    lanes: i32,
+    width: f32,
+    max_idx: f32,
+    coloring: Coloring<'a>,
+) -> impl Iterator<Item = Vertex> + 'a {
+    let normal = sample.right() * width;
+    let up = *sample.up().as_ref();
+    let v = sample.index / max_idx;
+    let total_lanes = lanes * 2 + 1;
+    (-lanes..=lanes).map(move |lane| {
+        let u = lane as f32 / total_lanes as f32;
+        let pos = sample.position + normal * (u * width);
+        let color = match coloring {
+            Coloring::Uv => [(u + 0.5) / 2., v, w],
+            Coloring::Lane(lane_color) => lane_color(lane, v),
+            Coloring::Track => sample.color,
+        };
+        Vertex::new(*pos.coords.as_ref(), color).with_normal(up)
+    })
+}
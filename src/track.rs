@@ -1,132 +1,2186 @@
-use nalgebra::{Point3, Vector3, UnitQuaternion, Unit};
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use anyhow::Context;
+use anyhow::{format_err, Result};
+use core::cell::OnceCell;
+use core::ops::{Div, Mul, Sub};
+use nalgebra::{Matrix4, Point3, Vector2, Vector3, UnitQuaternion};
+#[cfg(feature = "std")]
+use std::io::{BufRead, BufReader, Read, Write};
+pub use crate::curve::*;
+use crate::curve::{bank_angle, color, lerp, orientation, shortest_arc, wrap_angle};
 
-/// Control point for a track
-pub struct TrackControl {
-    pub position: Point3<f32>,
-    pub direction: Vector3<f32>,
-    pub angle: f32,
+/// Up vector of the track surface when heading along `derivative` with banking `angle`
+fn up_vector(derivative: &Vector3<f32>, angle: f32) -> Vector3<f32> {
+    orientation(derivative, angle, &Vector3::x()).transform_vector(&Vector3::y())
 }
 
-impl TrackControl {
-    /// Create a new TrackControl
-    pub fn new(position: Point3<f32>, direction: Vector3<f32>, angle: f32) -> Self {
+/// Banking angle which brings the up vector of the track surface, when heading along
+/// `derivative`, as close as possible to `up`
+fn bank_toward(derivative: &Vector3<f32>, up: &Vector3<f32>) -> f32 {
+    let base = orientation(derivative, 0., &Vector3::x());
+    let base_up = base.transform_vector(&Vector3::y());
+    let base_right = base.transform_vector(&Vector3::z());
+    up.dot(&base_right).atan2(up.dot(&base_up))
+}
+
+/// Set each control's banking so that its up vector matches `ups`, choosing among equivalent
+/// angles so that the roll from one control to the next stays closest to `rolls`
+fn match_banking(controls: &mut [TrackControl], ups: &[Vector3<f32>], rolls: &[f32]) {
+    for idx in 0..controls.len() {
+        let angle = bank_toward(&controls[idx].direction, &ups[idx]);
+        controls[idx].angle = match idx {
+            0 => angle,
+            _ => {
+                let expected = controls[idx - 1].angle + rolls[idx];
+                expected + wrap_angle(angle - expected)
+            }
+        };
+    }
+}
+
+/// Banking angles of a collection of track controls interpolated as a natural cubic spline, so
+/// that the rate of roll is continuous across controls rather than easing to a stop at each one.
+/// Rolls between controls follow the shortest arc unless `full_roll` is set, as with easing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankingSpline {
+    /// Angle at each control, unwrapped to be continuous along the track
+    angles: Vec<f32>,
+    /// Rate of change of the angle per unit of index at each control
+    slopes: Vec<f32>,
+}
+
+impl BankingSpline {
+    pub fn new(controls: &[TrackControl]) -> Self {
+        let mut angles: Vec<f32> = controls.first().map(|c| c.angle).into_iter().collect();
+        for pair in controls.windows(2) {
+            let roll = bank_angle(&pair[0], &pair[1], 1., Easing::Linear) - pair[0].angle;
+            angles.push(angles[angles.len() - 1] + roll);
+        }
+
+        let n = angles.len();
+        let slopes = match n {
+            0 | 1 => vec![0.; n],
+            _ => {
+                let mut diag = vec![4.; n];
+                diag[0] = 2.;
+                diag[n - 1] = 2.;
+                let rhs = (0..n)
+                    .map(|idx| (angles[(idx + 1).min(n - 1)] - angles[idx.saturating_sub(1)]) * 3.)
+                    .collect();
+                solve_tridiagonal(&vec![1.; n], &mut diag, &vec![1.; n], rhs)
+            }
+        };
+
+        Self { angles, slopes }
+    }
+
+    /// Banking angle at index `i`, or `None` past the ends of the track
+    pub fn angle(&self, i: f32) -> Option<f32> {
+        use core::convert::TryInto;
+        if i == self.angles.len().saturating_sub(1) as f32 {
+            return self.angles.last().copied();
+        }
+        let base: usize = (i as i64).try_into().ok()?;
+        let (a0, a1) = (*self.angles.get(base)?, *self.angles.get(base + 1)?);
+        let (m0, m1) = (self.slopes[base], self.slopes[base + 1]);
+
+        // Cubic Hermite basis
+        let t = i.fract();
+        let (t2, t3) = (t * t, t * t * t);
+        Some(
+            a0 * (2. * t3 - 3. * t2 + 1.)
+                + m0 * (t3 - 2. * t2 + t)
+                + a1 * (3. * t2 - 2. * t3)
+                + m1 * (t3 - t2),
+        )
+    }
+}
+
+/// Track follower
+pub struct TrackFollower<'a> {
+    controls: &'a [TrackControl],
+    pub i: f32,
+    /// Distance between samples; negative to travel backward, toward `i = 0`
+    pub rate: f32,
+    /// Coefficients of the segment most recently sampled, and its index
+    segment: Option<(usize, SegmentEval)>,
+    /// Banking across the whole track, instead of eased per segment
+    banking: Option<BankingSpline>,
+    /// Wrap around past either end instead of stopping
+    looping: bool,
+}
+
+impl<'a> TrackFollower<'a> {
+    /// Create a new follower at `i = 0`
+    pub fn new(controls: &'a [TrackControl], rate: f32) -> Self {
         Self {
-            position,
-            direction,
+            controls,
+            rate,
+            i: 0.,
+            segment: None,
+            banking: None,
+            looping: false,
+        }
+    }
+
+    /// Wrap around to the other end of the track instead of stopping at either end, as on a
+    /// closed track
+    pub fn with_looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Interpolate banking with a `BankingSpline` over all of the controls
+    pub fn with_banking_spline(mut self) -> Self {
+        self.banking = Some(BankingSpline::new(self.controls));
+        self
+    }
+
+    /// Sample at the current position, reusing the segment's coefficients between calls
+    fn sample(&mut self) -> Option<TrackSample> {
+        use core::convert::TryInto;
+        let base: usize = (self.i as i64).try_into().ok()?;
+        self.sample_segment(base, self.i.fract())
+    }
+
+    /// Sample `i` along the segment starting at control `base`
+    fn sample_segment(&mut self, base: usize, i: f32) -> Option<TrackSample> {
+        let begin = self.controls.get(base)?;
+        let end = self.controls.get(base + 1)?;
+        let eval = match self.segment {
+            Some((idx, eval)) if idx == base => eval,
+            _ => {
+                let eval = SegmentEval::new(begin, end);
+                self.segment = Some((base, eval));
+                eval
+            }
+        };
+        let angle = match &self.banking {
+            Some(banking) => banking.angle(base as f32 + i)?,
+            None => bank_angle(begin, end, i, Easing::default()),
+        };
+        Some(TrackSample {
+            position: eval.eval(i),
+            derivative: eval.eval_deriv(i),
             angle,
+            index: base as f32 + i,
+            color: color(begin, end, i, Easing::default()),
+        })
+    }
+
+    /// Advance like `next`, but once past either end, clamp to it and report the final sample
+    /// instead of stopping. Repeated calls at the end keep returning it. Looping followers never
+    /// reach an end. `None` only if the track has no segments.
+    pub fn step(&mut self) -> Option<StepResult> {
+        let segments = self.controls.len().saturating_sub(1);
+        if segments == 0 {
+            return None;
         }
+        let length = segments as f32;
+        let at_start = self.i < 0. || (self.i == 0. && self.rate < 0.);
+        if !self.looping && (at_start || self.i >= length) {
+            let (base, i) = match at_start {
+                true => (0, 0.),
+                false => (segments - 1, 1.),
+            };
+            self.i = base as f32 + i;
+            return self.sample_segment(base, i).map(StepResult::ReachedEnd);
+        }
+        self.next().map(StepResult::Sample)
     }
 
-    /// The control point in front of this track control
-    pub fn front_ctrlp(&self) -> Point3<f32> {
-        self.position + self.direction
+    /// Write the next samples into `out`, until it is full or the track ends. Returns the number of
+    /// samples written.
+    pub fn fill(&mut self, out: &mut [TrackSample]) -> usize {
+        let mut written = 0;
+        for slot in out.iter_mut() {
+            match self.next() {
+                Some(sample) => *slot = sample,
+                None => break,
+            }
+            written += 1;
+        }
+        written
     }
+}
 
-    /// The control point behind this track control
-    pub fn back_ctrlp(&self) -> Point3<f32> {
-        self.position - self.direction
+/// Outcome of `TrackFollower::step`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepResult {
+    /// A sample along the track, after which the follower advanced
+    Sample(TrackSample),
+    /// The follower is held at an end of the track; this is the sample there
+    ReachedEnd(TrackSample),
+}
+
+impl Iterator for TrackFollower<'_> {
+    type Item = TrackSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let length = self.controls.len().saturating_sub(1) as f32;
+        if self.looping && length > 0. {
+            self.i -= (self.i / length).floor() * length;
+        }
+        if self.i < 0. {
+            return None;
+        }
+        let sample = self.sample()?;
+        self.i += self.rate / sample.derivative.magnitude();
+        Some(sample)
     }
 }
 
-/// Sample of a track
-pub struct TrackSample {
-    pub position: Point3<f32>,
-    pub derivative: Vector3<f32>,
-    pub angle: f32,
-    pub index: f32,
+/// Clear `out` and fill it with a sample at each of `params`, reusing its allocation. Params
+/// outside of the track are skipped.
+pub fn sample_into(controls: &[TrackControl], params: &[f32], out: &mut Vec<TrackSample>) {
+    out.clear();
+    out.extend(params.iter().filter_map(|&i| sample_collection(controls, i)));
 }
 
-impl TrackSample {
-    pub fn quaternion(&self, axis: &Vector3<f32>) -> UnitQuaternion<f32> {
-        UnitQuaternion::from_axis_angle(&Unit::new_normalize(self.derivative), self.angle) *
-        UnitQuaternion::rotation_between(axis, &self.derivative).unwrap()
+/// Segment of a track graph, running from one node's control to another's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Branching track, where each node is a control and each edge a segment between two of them.
+/// A node with several outgoing edges is a switch; they all leave along the node's direction.
+#[derive(Debug, Clone, Default)]
+pub struct TrackGraph {
+    pub nodes: Vec<TrackControl>,
+    pub edges: Vec<TrackEdge>,
+}
+
+impl TrackGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node, returning its index
+    pub fn add_node(&mut self, control: TrackControl) -> usize {
+        self.nodes.push(control);
+        self.nodes.len() - 1
+    }
+
+    /// Add a segment between two nodes, returning its index
+    pub fn add_edge(&mut self, from: usize, to: usize) -> usize {
+        self.edges.push(TrackEdge { from, to });
+        self.edges.len() - 1
+    }
+
+    /// Indices of the edges leaving `node`
+    pub fn outgoing(&self, node: usize) -> Vec<usize> {
+        (0..self.edges.len())
+            .filter(|&edge| self.edges[edge].from == node)
+            .collect()
+    }
+
+    /// Sample along `edge`, where `i` is in [0..1]
+    pub fn sample(&self, edge: usize, i: f32) -> Option<TrackSample> {
+        let edge = self.edges.get(edge)?;
+        Some(sample(self.nodes.get(edge.from)?, self.nodes.get(edge.to)?, i))
+    }
+
+    /// Follow the graph from the start of `edge` at `rate`. At each junction `choose` is given
+    /// the node and its outgoing edges, and returns the position of the edge to take within
+    /// them; the follower stops at dead ends or if the position is out of range.
+    pub fn follower<F>(&self, edge: usize, rate: f32, choose: F) -> GraphFollower<'_, F>
+    where
+        F: FnMut(usize, &[usize]) -> usize,
+    {
+        GraphFollower {
+            graph: self,
+            edge: Some(edge),
+            i: 0.,
+            rate,
+            choose,
+        }
     }
 }
 
-// Smoothe step from [0..1] to [0..1]
-fn smooth_step(i: f32) -> f32 {
-    i * i * (3. - 2. * i)
+/// Track follower for a TrackGraph
+pub struct GraphFollower<'a, F> {
+    graph: &'a TrackGraph,
+    /// Edge currently being followed, if not stopped
+    pub edge: Option<usize>,
+    /// Position along the current edge
+    pub i: f32,
+    pub rate: f32,
+    choose: F,
 }
 
-/// Sample between two track controls
-pub fn sample(begin: &TrackControl, end: &TrackControl, i: f32) -> TrackSample {
-    let position = spline(begin, end, i);
-    let derivative = spline_deriv(begin, end, i);
-    let angle = lerp(begin.angle, end.angle, smooth_step(i));
-    TrackSample {
-        position,
-        derivative,
-        angle,
-        index: i,
+impl<F: FnMut(usize, &[usize]) -> usize> Iterator for GraphFollower<'_, F> {
+    type Item = TrackSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge = self.edge?;
+        let sample = self.graph.sample(edge, self.i)?;
+        self.i += self.rate / sample.derivative.magnitude();
+
+        // Move on to the next edge, carrying over the remainder
+        if self.i >= 1. {
+            let node = self.graph.edges[edge].to;
+            let outgoing = self.graph.outgoing(node);
+            self.edge = match outgoing.len() {
+                0 => None,
+                1 => Some(outgoing[0]),
+                _ => outgoing.get((self.choose)(node, &outgoing)).copied(),
+            };
+            self.i -= 1.;
+        }
+
+        Some(sample)
     }
 }
 
-/// Sample from a collection of track controls
-pub fn sample_collection(controls: &[TrackControl], i: f32) -> Option<TrackSample> {
-    use std::convert::TryInto;
-    let base: usize = (i as i64).try_into().ok()?;
-    let begin = controls.get(base)?;
-    let end = controls.get(base+1)?;
-    let mut sample = sample(begin, end, i.fract());
-    sample.index = i;
+/// Counts laps of a looping track from a parameter which wraps back towards zero at the end of
+/// each lap, such as a sampling index or distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LapCounter {
+    /// Backwards jump in the parameter which counts as a wrap; smaller jumps are treated as jitter
+    pub threshold: f32,
+    last: Option<f32>,
+    laps: u32,
+}
+
+impl LapCounter {
+    /// Create a new LapCounter; `threshold` should be around half the parameter's range
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            last: None,
+            laps: 0,
+        }
+    }
+
+    /// Feed the current parameter, returning true if it just wrapped to begin a new lap
+    pub fn update(&mut self, i: f32) -> bool {
+        let wrapped = self.last.is_some_and(|last| last - i > self.threshold);
+        if wrapped {
+            self.laps += 1;
+        }
+        self.last = Some(i);
+        wrapped
+    }
+
+    /// Number of laps completed
+    pub fn laps(&self) -> u32 {
+        self.laps
+    }
+}
+
+/// Chords per segment used when measuring a track
+const ARC_LENGTH_STEPS: usize = 64;
+
+/// Cumulative distance along a collection of track controls, for converting between distance and
+/// the sampling index
+#[derive(Debug, Clone)]
+pub struct ArcLengthTable {
+    steps: usize,
+    /// Distance at `steps` evenly spaced parameters per segment, plus the end of the track
+    cumulative: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    /// Measure `controls`, approximating each segment by `steps` straight chords
+    pub fn new(controls: &[TrackControl], steps: usize) -> Self {
+        let steps = steps.max(1);
+        let mut cumulative = vec![0.];
+        let mut distance = 0.;
+        for pair in controls.windows(2) {
+            let eval = SegmentEval::new(&pair[0], &pair[1]);
+            let mut last = eval.eval(0.);
+            for step in 1..=steps {
+                let position = eval.eval(step as f32 / steps as f32);
+                distance += (position - last).magnitude();
+                cumulative.push(distance);
+                last = position;
+            }
+        }
+        Self { steps, cumulative }
+    }
+
+    /// Total length of the track
+    pub fn length(&self) -> f32 {
+        *self.cumulative.last().unwrap()
+    }
+
+    /// Number of segments measured
+    pub fn segments(&self) -> usize {
+        (self.cumulative.len() - 1) / self.steps
+    }
+
+    /// Distance along the track at sampling index `i`, clamped to its ends
+    pub fn distance_at(&self, i: f32) -> f32 {
+        let steps = (i * self.steps as f32).clamp(0., (self.cumulative.len() - 1) as f32);
+        let chord = (steps as usize).min(self.cumulative.len().saturating_sub(2));
+        match self.cumulative.get(chord + 1) {
+            Some(end) => lerp(self.cumulative[chord], *end, steps - chord as f32),
+            None => 0.,
+        }
+    }
+}
+
+/// Segment index and local parameter at distance `d` along the track. Distances outside of the
+/// track are clamped to its ends.
+pub fn segment_at_distance(table: &ArcLengthTable, d: f32) -> (usize, f32) {
+    let segments = table.segments();
+    if segments == 0 {
+        return (0, 0.);
+    }
+
+    // Find the chord containing `d`
+    let cumulative = &table.cumulative;
+    let chord = cumulative
+        .partition_point(|&distance| distance <= d)
+        .clamp(1, cumulative.len() - 1)
+        - 1;
+    let (start, end) = (cumulative[chord], cumulative[chord + 1]);
+    let along = match end > start {
+        true => ((d - start) / (end - start)).clamp(0., 1.),
+        false => 0.,
+    };
+
+    let segment = chord / table.steps;
+    let local = ((chord % table.steps) as f32 + along) / table.steps as f32;
+    (segment, local)
+}
+
+/// Sample at distance `d` along the track, clamped to its ends
+pub fn sample_at_distance(
+    controls: &[TrackControl],
+    table: &ArcLengthTable,
+    d: f32,
+) -> Option<TrackSample> {
+    let (segment, local) = segment_at_distance(table, d);
+    let mut sample = sample(controls.get(segment)?, controls.get(segment + 1)?, local);
+    sample.index = segment as f32 + local;
     Some(sample)
 }
 
-/// Derivative between two track controls
-pub fn spline_deriv(begin: &TrackControl, end: &TrackControl, i: f32) -> Vector3<f32> {
-    let iv = 1. - i; // i inverse
-    let p0 = begin.position.coords;
-    let p1 = begin.front_ctrlp().coords;
-    let p2 = end.back_ctrlp().coords;
-    let p3 = end.position.coords;
-    (3. * iv.powf(2.) * (p1 - p0))
-        + (6. * iv * i * (p2 - p1))
-        + (3. * i.powf(2.) * (p3 - p2))
+/// Sample at `t` in [0, 1] across the whole track by arc length, so that `t = 0.5` is halfway
+/// along it. Measures the track on every call; prefer `Track::sample_normalized` when sampling
+/// repeatedly.
+pub fn sample_normalized(controls: &[TrackControl], t: f32) -> Option<TrackSample> {
+    let table = ArcLengthTable::new(controls, ARC_LENGTH_STEPS);
+    sample_at_distance(controls, &table, t.clamp(0., 1.) * table.length())
 }
 
-/// Position between two track controls
-pub fn spline(
-    begin: &TrackControl,
-    end: &TrackControl,
-    i: f32,
-) -> Point3<f32> {
-    let iv = 1. - i; // i inverse
-    let p0 = begin.position.coords;
-    let p1 = begin.front_ctrlp().coords;
-    let p2 = end.back_ctrlp().coords;
-    let p3 = end.position.coords;
-    let coords = (iv.powf(3.) * p0)
-        + (3. * iv.powf(2.) * i * p1)
-        + (3. * iv * i.powf(2.) * p2)
-        + (i.powf(3.) * p3);
-    Point3 { coords }
+/// Sample at the point on the track nearest to `point`
+pub fn closest_point(controls: &[TrackControl], point: Point3<f32>) -> Option<TrackSample> {
+    const COARSE_STEPS: usize = 16;
+    const NEWTON_ITERATIONS: usize = 5;
+
+    let mut best: Option<(f32, f32)> = None;
+    for (segment, pair) in controls.windows(2).enumerate() {
+        let eval = SegmentEval::new(&pair[0], &pair[1]);
+        let dist_sq = |i: f32| (eval.eval(i) - point).magnitude_squared();
+
+        // Coarse scan for a starting guess, to avoid converging on a far away local minimum
+        let mut i = (0..=COARSE_STEPS)
+            .map(|step| step as f32 / COARSE_STEPS as f32)
+            .min_by(|a, b| dist_sq(*a).partial_cmp(&dist_sq(*b)).unwrap())
+            .unwrap();
+
+        // Newton's method on the derivative of the squared distance
+        for _ in 0..NEWTON_ITERATIONS {
+            let offset = eval.eval(i) - point;
+            let deriv = eval.eval_deriv(i);
+            let slope = deriv.magnitude_squared() + offset.dot(&eval.eval_deriv2(i));
+            if slope.abs() <= f32::EPSILON {
+                break;
+            }
+            i = (i - offset.dot(&deriv) / slope).clamp(0., 1.);
+        }
+
+        let dist = dist_sq(i);
+        if best.is_none_or(|(best_dist, _)| dist < best_dist) {
+            best = Some((dist, segment as f32 + i));
+        }
+    }
+
+    let (_, index) = best?;
+    let base = (index as usize).min(controls.len() - 2);
+    let mut sample = sample(&controls[base], &controls[base + 1], index - base as f32);
+    sample.index = index;
+    Some(sample)
 }
 
-fn lerp(a: f32, b: f32, i: f32) -> f32 {
-    a * (1. - i) + b * i
+/// A collection of track controls which keeps its measurements cached
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+    controls: Vec<TrackControl>,
+    /// Built on first use, and cleared whenever the controls change
+    table: OnceCell<ArcLengthTable>,
+    pub markers: Vec<TrackMarker>,
 }
 
-/// Track follower
-pub struct TrackFollower<'a> {
+/// Named point along a track, for triggering events
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackMarker {
+    /// Distance along the track
+    pub distance: f32,
+    pub name: String,
+}
+
+impl Track {
+    /// Create a new Track from `controls`
+    pub fn new(controls: Vec<TrackControl>) -> Self {
+        Self {
+            controls,
+            table: OnceCell::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    pub fn controls(&self) -> &[TrackControl] {
+        &self.controls
+    }
+
+    /// Mutable access to the controls, which invalidates the cached measurements
+    pub fn controls_mut(&mut self) -> &mut Vec<TrackControl> {
+        self.table.take();
+        &mut self.controls
+    }
+
+    /// Append a control to the end of the track
+    pub fn push(&mut self, control: TrackControl) {
+        self.controls_mut().push(control);
+    }
+
+    /// Take back the controls
+    pub fn into_controls(self) -> Vec<TrackControl> {
+        self.controls
+    }
+
+    /// Arc length table of the track, measured on first use
+    pub fn table(&self) -> &ArcLengthTable {
+        self.table.get_or_init(|| ArcLengthTable::new(&self.controls, ARC_LENGTH_STEPS))
+    }
+
+    /// Total length of the track
+    pub fn length(&self) -> f32 {
+        self.table().length()
+    }
+
+    /// Sample at index `i`, as with `sample_collection`
+    pub fn sample(&self, i: f32) -> Option<TrackSample> {
+        sample_collection(&self.controls, i)
+    }
+
+    /// Sample at distance `d` along the track, clamped to its ends
+    pub fn sample_at_distance(&self, d: f32) -> Option<TrackSample> {
+        sample_at_distance(&self.controls, self.table(), d)
+    }
+
+    /// Sample at `t` in [0, 1] across the whole track by arc length
+    pub fn sample_normalized(&self, t: f32) -> Option<TrackSample> {
+        self.sample_at_distance(t.clamp(0., 1.) * self.length())
+    }
+
+    /// Rise over run at distance `d` along the track, clamped on vertical sections
+    pub fn grade_at_distance(&self, d: f32) -> f32 {
+        grade_at_distance(&self.controls, self.table(), d)
+    }
+
+    /// Pitch above the horizontal in radians at distance `d` along the track
+    pub fn pitch_at_distance(&self, d: f32) -> f32 {
+        pitch_at_distance(&self.controls, self.table(), d)
+    }
+
+    /// Sample at the point on the track nearest to `point`
+    pub fn closest_point(&self, point: Point3<f32>) -> Option<TrackSample> {
+        closest_point(&self.controls, point)
+    }
+
+    /// Distance along the track of the point nearest to `point`
+    pub fn progress(&self, point: Point3<f32>) -> f32 {
+        match self.closest_point(point) {
+            Some(sample) => self.table().distance_at(sample.index),
+            None => 0.,
+        }
+    }
+
+    /// Whether the track ends where it begins
+    pub fn is_closed(&self) -> bool {
+        is_closed(&self.controls)
+    }
+
+    /// Follow the track from the start at `rate`
+    pub fn follower(&self, rate: f32) -> TrackFollower<'_> {
+        TrackFollower::new(&self.controls, rate)
+    }
+
+    /// Place a marker named `name` at distance `distance` along the track
+    pub fn add_marker(&mut self, distance: f32, name: impl Into<String>) {
+        self.markers.push(TrackMarker {
+            distance,
+            name: name.into(),
+        });
+    }
+
+    /// Markers crossed moving forward from `prev_distance` to `current_distance`, in the order
+    /// they were passed. A backwards jump of over half the track is taken as wrapping around
+    /// the end of a closed track; smaller backwards moves pass no markers.
+    pub fn passed_markers(&self, prev_distance: f32, current_distance: f32) -> Vec<&TrackMarker> {
+        let between = |from: f32, to: f32| {
+            let mut passed: Vec<&TrackMarker> = self
+                .markers
+                .iter()
+                .filter(|marker| marker.distance > from && marker.distance <= to)
+                .collect();
+            passed.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+            passed
+        };
+
+        if current_distance >= prev_distance {
+            between(prev_distance, current_distance)
+        } else if prev_distance - current_distance > self.length() / 2. {
+            let mut passed = between(prev_distance, f32::INFINITY);
+            passed.extend(between(f32::NEG_INFINITY, current_distance));
+            passed
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl From<Vec<TrackControl>> for Track {
+    fn from(controls: Vec<TrackControl>) -> Self {
+        Self::new(controls)
+    }
+}
+
+/// Largest grade reported, for vertical sections of track
+const MAX_GRADE: f32 = 1000.;
+
+/// Rise over run of the track at distance `d`, clamped to +/-1000 on vertical sections
+pub fn grade_at_distance(controls: &[TrackControl], table: &ArcLengthTable, d: f32) -> f32 {
+    match sample_at_distance(controls, table, d) {
+        Some(sample) => {
+            let tangent = sample.derivative;
+            let run = tangent.xz().magnitude();
+            match run > f32::EPSILON {
+                true => (tangent.y / run).clamp(-MAX_GRADE, MAX_GRADE),
+                false => MAX_GRADE.copysign(tangent.y),
+            }
+        }
+        None => 0.,
+    }
+}
+
+/// Pitch of the track above the horizontal in radians at distance `d`
+pub fn pitch_at_distance(controls: &[TrackControl], table: &ArcLengthTable, d: f32) -> f32 {
+    match sample_at_distance(controls, table, d) {
+        Some(sample) => {
+            let tangent = sample.derivative;
+            tangent.y.atan2(tangent.xz().magnitude())
+        }
+        None => 0.,
+    }
+}
+
+/// Fastest speed at distance `d` along the track which keeps the lateral acceleration in the
+/// turns under `max_lateral_accel`. Infinite on straights.
+pub fn speed_limit(
+    controls: &[TrackControl],
+    table: &ArcLengthTable,
+    max_lateral_accel: f32,
+    d: f32,
+) -> f32 {
+    let (segment, local) = segment_at_distance(table, d);
+    match (controls.get(segment), controls.get(segment + 1)) {
+        (Some(begin), Some(end)) => (max_lateral_accel / curvature(begin, end, local)).sqrt(),
+        _ => f32::INFINITY,
+    }
+}
+
+/// Track follower which starts from rest and moves as fast as a speed limit allows, given limits
+/// on how quickly it may speed up and slow down. A follower which can't get moving, because
+/// `max_accel` or the speed limit is zero, stays at the start of the track.
+pub struct ProfiledFollower<'a> {
     controls: &'a [TrackControl],
-    pub i: f32,
-    pub rate: f32,
+    table: ArcLengthTable,
+    /// Achievable speed at each of the table's distances
+    speeds: Vec<f32>,
+    /// Time at which each of the table's distances is reached
+    times: Vec<f32>,
+    pub time: f32,
+    pub dt: f32,
 }
 
-impl<'a> TrackFollower<'a> {
-    /// Create a new follower at `i = 0`
-    pub fn new(controls: &'a [TrackControl], rate: f32) -> Self {
-        Self { controls, rate, i: 0. }
+impl<'a> ProfiledFollower<'a> {
+    /// Plan a trip along `controls`, given the `speed_limit` at each distance along the track,
+    /// which advances by `dt` seconds per step
+    pub fn new(
+        controls: &'a [TrackControl],
+        speed_limit: impl Fn(f32) -> f32,
+        max_accel: f32,
+        max_decel: f32,
+        dt: f32,
+    ) -> Self {
+        let table = ArcLengthTable::new(controls, ARC_LENGTH_STEPS);
+        let distances = &table.cumulative;
+        let mut speeds: Vec<f32> = distances.iter().map(|&d| speed_limit(d).max(0.)).collect();
+        speeds[0] = 0.;
+
+        // Forward pass; accelerate as hard as allowed out of each slow point
+        for idx in 1..speeds.len() {
+            let ds = distances[idx] - distances[idx - 1];
+            let reachable = (speeds[idx - 1].powi(2) + 2. * max_accel * ds).sqrt();
+            speeds[idx] = speeds[idx].min(reachable);
+        }
+
+        // Backward pass; brake early enough to make it into each slow point
+        for idx in (0..speeds.len() - 1).rev() {
+            let ds = distances[idx + 1] - distances[idx];
+            let stoppable = (speeds[idx + 1].powi(2) + 2. * max_decel * ds).sqrt();
+            speeds[idx] = speeds[idx].min(stoppable);
+        }
+
+        // Time taken between each point, assuming constant acceleration. Points which can't be
+        // reached at all are reached at infinity.
+        let mut times = vec![0.];
+        for idx in 1..speeds.len() {
+            let ds = distances[idx] - distances[idx - 1];
+            let average = (speeds[idx - 1] + speeds[idx]) / 2.;
+            let elapsed = match average > 0. {
+                true => ds / average,
+                false if ds > 0. => f32::INFINITY,
+                false => 0.,
+            };
+            times.push(times[idx - 1] + elapsed);
+        }
+
+        Self {
+            controls,
+            table,
+            speeds,
+            times,
+            time: 0.,
+            dt,
+        }
+    }
+
+    /// Distance along the track and speed at `time`
+    fn state_at(&self, time: f32) -> Option<(f32, f32)> {
+        // A track of fewer than two controls has nowhere to go
+        if self.times.len() < 2 || time > *self.times.last()? {
+            return None;
+        }
+
+        // Within each interval the speed changes linearly with time
+        let idx = self.times.partition_point(|&t| t <= time).clamp(1, self.times.len() - 1) - 1;
+        let (v0, v1) = (self.speeds[idx], self.speeds[idx + 1]);
+        let duration = self.times[idx + 1] - self.times[idx];
+        let t = time - self.times[idx];
+        let accel = match duration > 0. {
+            true => (v1 - v0) / duration,
+            false => 0.,
+        };
+        let distance = self.table.cumulative[idx] + v0 * t + accel * t * t / 2.;
+        Some((distance, v0 + accel * t))
+    }
+
+    /// Speed at the current time, or zero once past the end
+    pub fn speed(&self) -> f32 {
+        self.state_at(self.time).map(|(_, speed)| speed).unwrap_or(0.)
+    }
+
+    /// Total time taken to travel the track, which is infinite if the follower can't get moving
+    pub fn duration(&self) -> f32 {
+        *self.times.last().unwrap()
+    }
+
+    /// Speed at points along the track as `(distance, speed)` pairs, for use with `lap_time`
+    pub fn speed_profile(&self) -> Vec<(f32, f32)> {
+        self.table
+            .cumulative
+            .iter()
+            .copied()
+            .zip(self.speeds.iter().copied())
+            .collect()
     }
 }
 
-impl Iterator for TrackFollower<'_> {
+impl Iterator for ProfiledFollower<'_> {
     type Item = TrackSample;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let sample = sample_collection(self.controls, self.i)?;
-        self.i += self.rate / sample.derivative.magnitude();
+        let (distance, _) = self.state_at(self.time)?;
+        let sample = sample_at_distance(self.controls, &self.table, distance)?;
+        self.time += self.dt;
         Some(sample)
     }
 }
 
+/// Time taken to travel the whole track following `speed_profile`, given as `(distance, speed)`
+/// pairs sorted by distance. Speed changes linearly between the points of the profile and holds
+/// its value beyond either end, so a single point gives a constant speed, and two points at the
+/// same distance step from one speed to the other there. A track which can't be traveled,
+/// because the profile is empty or stops partway along, takes forever.
+pub fn lap_time(controls: &[TrackControl], speed_profile: &[(f32, f32)]) -> f32 {
+    let length = ArcLengthTable::new(controls, ARC_LENGTH_STEPS).length();
+    if length <= 0. {
+        return 0.;
+    }
+
+    // Speed at `d`, interpolated within the profile's interval containing `within`. Measuring
+    // each of the track's intervals from its middle keeps a step in the profile, where two points
+    // share a distance, from leaking into the interval before it.
+    let speed_at = |d: f32, within: f32| {
+        let idx = speed_profile.partition_point(|&(distance, _)| distance <= within);
+        match (
+            idx.checked_sub(1).map(|i| speed_profile[i]),
+            speed_profile.get(idx),
+        ) {
+            (Some((d0, v0)), Some(&(d1, v1))) => lerp(v0, v1, (d - d0) / (d1 - d0)),
+            (Some((_, v)), None) | (None, Some(&(_, v))) => v,
+            (None, None) => 0.,
+        }
+    };
+
+    // Break the track at each point of the profile, so speed is linear within each interval
+    let mut distances = vec![0.];
+    distances.extend(
+        speed_profile
+            .iter()
+            .map(|&(d, _)| d)
+            .filter(|&d| d > 0. && d < length),
+    );
+    distances.push(length);
+
+    // Assume constant acceleration within each interval, as ProfiledFollower does. Duplicate
+    // distances leave intervals of zero width, which take no time.
+    distances
+        .windows(2)
+        .filter(|pair| pair[1] > pair[0])
+        .map(|pair| {
+            let middle = (pair[0] + pair[1]) / 2.;
+            let average = (speed_at(pair[0], middle) + speed_at(pair[1], middle)) / 2.;
+            match average > 0. {
+                true => (pair[1] - pair[0]) / average,
+                false => f32::INFINITY,
+            }
+        })
+        .sum()
+}
+
+/// Write samples spaced roughly `resolution` apart along the track as CSV, one row per sample
+/// with the distance traveled, position, unit tangent, curvature and banking angle
+#[cfg(feature = "std")]
+pub fn export_csv(
+    controls: &[TrackControl],
+    resolution: f32,
+    mut writer: impl Write,
+) -> Result<()> {
+    writeln!(writer, "distance,x,y,z,tx,ty,tz,curvature,angle")?;
+    let mut distance = 0.;
+    let mut last: Option<Point3<f32>> = None;
+    for sample in TrackFollower::new(controls, resolution) {
+        if let Some(last) = last {
+            distance += (sample.position - last).magnitude();
+        }
+        last = Some(sample.position);
+
+        let base = sample.index as usize;
+        let curvature = curvature(&controls[base], &controls[base + 1], sample.index.fract());
+        let p = sample.position;
+        let t = sample.derivative.normalize();
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            distance, p.x, p.y, p.z, t.x, t.y, t.z, curvature, sample.angle
+        )?;
+    }
+    Ok(())
+}
+
+/// Transform of a cart riding the track at a constant `speed`, at each of `fps` frames per second
+/// up to and including the end. Orientations are x forward, y up and z to the right, banked with
+/// the track.
+pub fn bake_transforms(controls: &[TrackControl], fps: f32, speed: f32) -> Vec<Matrix4<f32>> {
+    if controls.len() < 2 || fps <= 0. || speed <= 0. {
+        return Vec::new();
+    }
+    let table = ArcLengthTable::new(controls, ARC_LENGTH_STEPS);
+    let length = table.length();
+    let step = speed / fps;
+    let frames = (length / step).ceil() as usize;
+    (0..=frames)
+        .filter_map(|frame| sample_at_distance(controls, &table, (frame as f32 * step).min(length)))
+        .map(|sample| sample.transform(&Vector3::x()))
+        .collect()
+}
+
+/// Write transforms from `bake_transforms` as JSON, `{"fps": .., "frames": [..]}`, with each
+/// frame's matrix as 16 numbers in column-major order
+#[cfg(feature = "std")]
+pub fn write_transforms_json(
+    transforms: &[Matrix4<f32>],
+    fps: f32,
+    mut writer: impl Write,
+) -> Result<()> {
+    write!(writer, "{{\"fps\":{},\"frames\":[", fps)?;
+    for (idx, transform) in transforms.iter().enumerate() {
+        if idx > 0 {
+            write!(writer, ",")?;
+        }
+        let values: Vec<String> = transform.as_slice().iter().map(|v| v.to_string()).collect();
+        write!(writer, "[{}]", values.join(","))?;
+    }
+    writeln!(writer, "]}}")?;
+    Ok(())
+}
+
+/// Write track controls as CSV in the format read by `from_csv`
+#[cfg(feature = "std")]
+pub fn to_csv(controls: &[TrackControl], mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "px,py,pz,dx,dy,dz,angle")?;
+    for control in controls {
+        let (p, d) = (control.position, control.direction);
+        writeln!(writer, "{},{},{},{},{},{},{}", p.x, p.y, p.z, d.x, d.y, d.z, control.angle)?;
+    }
+    Ok(())
+}
+
+/// Read track controls from CSV, with a header row followed by one `px,py,pz,dx,dy,dz,angle`
+/// row per control
+#[cfg(feature = "std")]
+pub fn from_csv(reader: impl Read) -> Result<Vec<TrackControl>> {
+    let mut controls = Vec::new();
+    for (idx, line) in BufReader::new(reader).lines().enumerate().skip(1) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let values = line
+            .split(',')
+            .map(|v| v.trim().parse::<f32>())
+            .collect::<Result<Vec<f32>, _>>()
+            .with_context(|| format!("Line {}: invalid number", idx + 1))?;
+        match values[..] {
+            [px, py, pz, dx, dy, dz, angle] => controls.push(TrackControl::new(
+                Point3::new(px, py, pz),
+                Vector3::new(dx, dy, dz),
+                angle,
+            )),
+            _ => {
+                return Err(format_err!(
+                    "Line {}: expected px,py,pz,dx,dy,dz,angle",
+                    idx + 1
+                ))
+            }
+        }
+    }
+    if controls.len() < 2 {
+        return Err(format_err!("Track needs at least two controls"));
+    }
+    Ok(controls)
+}
+
+/// Plane onto which 2D coordinates are placed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Plane {
+    /// Vertical plane, with 2D y pointing down as in SVG
+    XY,
+    /// Ground plane, seen from above
+    XZ,
+}
+
+impl Plane {
+    /// Place an SVG coordinate on this plane
+    fn place(&self, point: Vector2<f32>) -> Vector3<f32> {
+        match self {
+            Plane::XY => Vector3::new(point.x, -point.y, 0.),
+            Plane::XZ => Vector3::new(point.x, 0., point.y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SvgToken {
+    Command(char),
+    Number(f32),
+}
+
+/// Split the `d` attribute of an SVG path into commands and numbers
+fn svg_tokens(d: &str) -> Result<Vec<SvgToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = d.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else if c.is_ascii_alphabetic() {
+            tokens.push(SvgToken::Command(c));
+            chars.next();
+        } else if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') {
+            // A sign may only lead, a second decimal point starts a new number, and an exponent
+            // may carry its own sign
+            let mut end = start;
+            let mut seen_dot = false;
+            let mut seen_exponent = false;
+            let mut prev = None;
+            while let Some(&(idx, c)) = chars.peek() {
+                let accept = match c {
+                    '-' | '+' => idx == start || matches!(prev, Some('e' | 'E')),
+                    '.' => !seen_dot && !seen_exponent,
+                    'e' | 'E' => idx != start && !seen_exponent,
+                    c => c.is_ascii_digit(),
+                };
+                if !accept {
+                    break;
+                }
+                seen_dot |= c == '.';
+                seen_exponent |= matches!(c, 'e' | 'E');
+                prev = Some(c);
+                end = idx + c.len_utf8();
+                chars.next();
+            }
+            let number = d[start..end]
+                .parse()
+                .map_err(|_| format_err!("Invalid number {:?} in SVG path", &d[start..end]))?;
+            tokens.push(SvgToken::Number(number));
+        } else {
+            return Err(format_err!("Unexpected {:?} in SVG path", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A point on an SVG path, with the handles of the curves entering and leaving it
+struct SvgNode {
+    position: Vector2<f32>,
+    incoming: Option<Vector2<f32>>,
+    outgoing: Option<Vector2<f32>>,
+}
+
+/// Import a track from the `d` attribute of an SVG path, placed on `plane`. Cubic curves (`C`,
+/// `S`) keep their shape where their handles are collinear, as from smooth nodes; otherwise the
+/// handle directions on either side of a node are averaged. Lines (`L`, `Z`) become straight
+/// segments.
+/// Banking angles are zero, and only a single subpath is supported.
+pub fn from_svg_path(d: &str, plane: Plane) -> Result<Vec<TrackControl>> {
+    let tokens = svg_tokens(d)?;
+    let mut nodes: Vec<SvgNode> = Vec::new();
+    let mut command = None;
+    let mut current = Vector2::zeros();
+    let mut last_ctrlp: Option<Vector2<f32>> = None;
+    let mut idx = 0;
+
+    // Read the next coordinate pair, relative to `origin`
+    let point = |idx: &mut usize, origin: Vector2<f32>| -> Result<Vector2<f32>> {
+        match tokens.get(*idx..*idx + 2) {
+            Some([SvgToken::Number(x), SvgToken::Number(y)]) => {
+                *idx += 2;
+                Ok(origin + Vector2::new(*x, *y))
+            }
+            _ => Err(format_err!("Expected a coordinate pair in SVG path")),
+        }
+    };
+
+    while idx < tokens.len() {
+        if let SvgToken::Command(c) = tokens[idx] {
+            command = Some(c);
+            idx += 1;
+        }
+        let c = command.ok_or_else(|| format_err!("Expected a command in SVG path"))?;
+        if nodes.is_empty() && !c.eq_ignore_ascii_case(&'M') {
+            return Err(format_err!("SVG path must begin with a move"));
+        }
+        let origin = match c.is_ascii_lowercase() {
+            true => current,
+            false => Vector2::zeros(),
+        };
+
+        let (position, handles) = match c.to_ascii_uppercase() {
+            'M' => {
+                if !nodes.is_empty() {
+                    return Err(format_err!("SVG path must have only one subpath"));
+                }
+                current = point(&mut idx, origin)?;
+                nodes.push(SvgNode {
+                    position: current,
+                    incoming: None,
+                    outgoing: None,
+                });
+
+                // Further coordinates are implicitly lines
+                command = Some(if c == 'm' { 'l' } else { 'L' });
+                continue;
+            }
+            'L' => (point(&mut idx, origin)?, None),
+            'Z' => {
+                command = None;
+                match nodes.first() {
+                    Some(first) if first.position != current => (first.position, None),
+                    _ => continue,
+                }
+            }
+            'C' => {
+                let ctrlp_a = point(&mut idx, origin)?;
+                let ctrlp_b = point(&mut idx, origin)?;
+                (point(&mut idx, origin)?, Some((ctrlp_a, ctrlp_b)))
+            }
+            'S' => {
+                let ctrlp_a = match last_ctrlp {
+                    Some(last) => current * 2. - last,
+                    None => current,
+                };
+                let ctrlp_b = point(&mut idx, origin)?;
+                (point(&mut idx, origin)?, Some((ctrlp_a, ctrlp_b)))
+            }
+            other => return Err(format_err!("Unsupported SVG path command {:?}", other)),
+        };
+
+        let (outgoing, incoming) = match handles {
+            Some((ctrlp_a, ctrlp_b)) => (ctrlp_a - current, position - ctrlp_b),
+            None => {
+                let third = (position - current) / 3.;
+                (third, third)
+            }
+        };
+        if let Some(last) = nodes.last_mut() {
+            last.outgoing = Some(outgoing);
+        }
+        nodes.push(SvgNode {
+            position,
+            incoming: Some(incoming),
+            outgoing: None,
+        });
+        last_ctrlp = handles.map(|(_, ctrlp_b)| ctrlp_b);
+        current = position;
+    }
+
+    if nodes.len() < 2 {
+        return Err(format_err!("SVG path must have at least two points"));
+    }
+
+    Ok(nodes
+        .iter()
+        .map(|node| {
+            let (direction, in_length, out_length) = match (node.incoming, node.outgoing) {
+                // Handles which aren't collinear are averaged, keeping each one's length
+                (Some(incoming), Some(outgoing)) => {
+                    let direction = (incoming + outgoing) / 2.;
+                    match direction.magnitude() {
+                        m if m > 0. => (
+                            direction,
+                            incoming.magnitude() / m,
+                            outgoing.magnitude() / m,
+                        ),
+                        _ => (direction, 1., 1.),
+                    }
+                }
+                (Some(handle), None) | (None, Some(handle)) => (handle, 1., 1.),
+                (None, None) => (Vector2::zeros(), 1., 1.),
+            };
+            TrackControl {
+                in_length,
+                out_length,
+                ..TrackControl::new(
+                    Point3::from(plane.place(node.position)),
+                    plane.place(direction),
+                    0.,
+                )
+            }
+        })
+        .collect())
+}
+
+/// Fit a track through `points` using Schneider's curve fitting algorithm, splitting only where
+/// needed to stay within `max_error` of every point. Banking angles are zero.
+pub fn fit(points: &[Point3<f32>], max_error: f32) -> Vec<TrackControl> {
+    let mut points = points.to_vec();
+    points.dedup();
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let last = points.len() - 1;
+    let start_tangent = (points[1] - points[0]).normalize();
+    let end_tangent = (points[last - 1] - points[last]).normalize();
+    let mut segments = Vec::new();
+    fit_cubic(&points, start_tangent, end_tangent, max_error, &mut segments);
+
+    // Join the segments' handles into controls; handles on either side of a split are collinear
+    let mut controls = vec![TrackControl::new(
+        segments[0][0],
+        segments[0][1] - segments[0][0],
+        0.,
+    )];
+    for (before, after) in segments.iter().zip(&segments[1..]) {
+        let direction = after[1] - after[0];
+        controls.push(TrackControl {
+            in_length: (before[3] - before[2]).magnitude() / direction.magnitude(),
+            ..TrackControl::new(after[0], direction, 0.)
+        });
+    }
+    let end = segments[segments.len() - 1];
+    controls.push(TrackControl::new(end[3], end[3] - end[2], 0.));
+    controls
+}
+
+/// Bezier control points of a single fitted segment
+type Cubic = [Point3<f32>; 4];
+
+/// Fit cubics to `points` recursively; `start_tangent` points into the curve from the first point,
+/// and `end_tangent` points back into the curve from the last point. Segments are pushed in order.
+fn fit_cubic(
+    points: &[Point3<f32>],
+    start_tangent: Vector3<f32>,
+    end_tangent: Vector3<f32>,
+    max_error: f32,
+    segments: &mut Vec<Cubic>,
+) {
+    const MAX_ITERATIONS: usize = 4;
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    if points.len() == 2 {
+        let dist = (last - first).magnitude() / 3.;
+        segments.push([
+            first,
+            first + start_tangent * dist,
+            last + end_tangent * dist,
+            last,
+        ]);
+        return;
+    }
+
+    // Parameterize by chord length and attempt a fit, refining the parameters if close
+    let mut params = chord_length_params(points);
+    let mut cubic = generate_cubic(points, &params, start_tangent, end_tangent);
+    let (mut error, mut split) = max_fit_error(points, &cubic, &params);
+    if error < max_error * max_error {
+        segments.push(cubic);
+        return;
+    }
+    if error < (max_error * 4.).powi(2) {
+        for _ in 0..MAX_ITERATIONS {
+            params = reparameterize(points, &params, &cubic);
+            cubic = generate_cubic(points, &params, start_tangent, end_tangent);
+            let (new_error, new_split) = max_fit_error(points, &cubic, &params);
+            error = new_error;
+            split = new_split;
+            if error < max_error * max_error {
+                segments.push(cubic);
+                return;
+            }
+        }
+    }
+
+    // Fitting failed, so split at the point of maximum error and fit each half
+    let center_tangent = (points[split - 1] - points[split + 1]).normalize();
+    fit_cubic(&points[..=split], start_tangent, center_tangent, max_error, segments);
+    fit_cubic(&points[split..], -center_tangent, end_tangent, max_error, segments);
+}
+
+/// Least-squares fit of the handle lengths of a cubic with the given end tangents
+fn generate_cubic(
+    points: &[Point3<f32>],
+    params: &[f32],
+    start_tangent: Vector3<f32>,
+    end_tangent: Vector3<f32>,
+) -> Cubic {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    let mut c = [[0f32; 2]; 2];
+    let mut x = [0f32; 2];
+    for (point, &u) in points.iter().zip(params) {
+        let [b0, b1, b2, b3] = bernstein(u);
+        let a0 = start_tangent * b1;
+        let a1 = end_tangent * b2;
+        c[0][0] += a0.dot(&a0);
+        c[0][1] += a0.dot(&a1);
+        c[1][1] += a1.dot(&a1);
+        let shortfall = point.coords - (first.coords * (b0 + b1) + last.coords * (b2 + b3));
+        x[0] += a0.dot(&shortfall);
+        x[1] += a1.dot(&shortfall);
+    }
+    c[1][0] = c[0][1];
+
+    // Fall back to a third of the chord if the fit is degenerate
+    let chord = (last - first).magnitude();
+    let epsilon = 1e-6 * chord;
+    let det_c0_c1 = c[0][0] * c[1][1] - c[1][0] * c[0][1];
+    let det_c0_x = c[0][0] * x[1] - c[1][0] * x[0];
+    let det_x_c1 = x[0] * c[1][1] - x[1] * c[0][1];
+    let (alpha_start, alpha_end) = if det_c0_c1 == 0. {
+        (0., 0.)
+    } else {
+        (det_x_c1 / det_c0_c1, det_c0_x / det_c0_c1)
+    };
+    let (alpha_start, alpha_end) = if alpha_start < epsilon || alpha_end < epsilon {
+        (chord / 3., chord / 3.)
+    } else {
+        (alpha_start, alpha_end)
+    };
+
+    [
+        first,
+        first + start_tangent * alpha_start,
+        last + end_tangent * alpha_end,
+        last,
+    ]
+}
+
+/// Cubic Bernstein basis at `u`
+fn bernstein(u: f32) -> [f32; 4] {
+    let iv = 1. - u;
+    [iv * iv * iv, 3. * iv * iv * u, 3. * iv * u * u, u * u * u]
+}
+
+fn eval_cubic(cubic: &Cubic, u: f32) -> Point3<f32> {
+    let [b0, b1, b2, b3] = bernstein(u);
+    Point3::from(
+        cubic[0].coords * b0 + cubic[1].coords * b1 + cubic[2].coords * b2 + cubic[3].coords * b3,
+    )
+}
+
+/// Parameters in [0..1] proportional to the distance along the polyline
+fn chord_length_params(points: &[Point3<f32>]) -> Vec<f32> {
+    let mut params = vec![0.];
+    for pair in points.windows(2) {
+        let last = params[params.len() - 1];
+        params.push(last + (pair[1] - pair[0]).magnitude());
+    }
+    let total = params[params.len() - 1];
+    params.iter().map(|p| p / total).collect()
+}
+
+/// Largest squared distance between a point and its position on the cubic, and that point's index
+fn max_fit_error(points: &[Point3<f32>], cubic: &Cubic, params: &[f32]) -> (f32, usize) {
+    let mut max = 0.;
+    let mut split = points.len() / 2;
+    for (idx, (point, &u)) in points.iter().zip(params).enumerate().skip(1) {
+        if idx == points.len() - 1 {
+            break;
+        }
+        let dist = (eval_cubic(cubic, u) - point).magnitude_squared();
+        if dist >= max {
+            max = dist;
+            split = idx;
+        }
+    }
+    (max, split)
+}
+
+/// Improve the parameters with a Newton-Raphson step toward each point's closest position
+fn reparameterize(points: &[Point3<f32>], params: &[f32], cubic: &Cubic) -> Vec<f32> {
+    // First and second derivative control points
+    let d1 = [
+        (cubic[1] - cubic[0]) * 3.,
+        (cubic[2] - cubic[1]) * 3.,
+        (cubic[3] - cubic[2]) * 3.,
+    ];
+    let d2 = [(d1[1] - d1[0]) * 2., (d1[2] - d1[1]) * 2.];
+
+    points
+        .iter()
+        .zip(params)
+        .map(|(point, &u)| {
+            let iv = 1. - u;
+            let q = eval_cubic(cubic, u) - point;
+            let q1 = d1[0] * (iv * iv) + d1[1] * (2. * iv * u) + d1[2] * (u * u);
+            let q2 = d2[0] * iv + d2[1] * u;
+            let denominator = q1.dot(&q1) + q.dot(&q2);
+            if denominator == 0. {
+                u
+            } else {
+                u - q.dot(&q1) / denominator
+            }
+        })
+        .collect()
+}
+
+/// Apply an affine transform to a track; positions are transformed as points and directions as
+/// vectors. Banking angles are relative to the tangent, so they are unchanged.
+pub fn transform(controls: &[TrackControl], m: &Matrix4<f32>) -> Vec<TrackControl> {
+    controls
+        .iter()
+        .map(|c| TrackControl {
+            position: m.transform_point(&c.position),
+            direction: m.transform_vector(&c.direction),
+            ..*c
+        })
+        .collect()
+}
+
+/// Move a track by `offset`
+pub fn translate(controls: &[TrackControl], offset: &Vector3<f32>) -> Vec<TrackControl> {
+    transform(controls, &Matrix4::new_translation(offset))
+}
+
+/// Rotate a track about the origin
+pub fn rotate(controls: &[TrackControl], rotation: &UnitQuaternion<f32>) -> Vec<TrackControl> {
+    transform(controls, &rotation.to_homogeneous())
+}
+
+/// Uniformly scale a track about the origin, including its width
+pub fn scale(controls: &[TrackControl], factor: f32) -> Vec<TrackControl> {
+    transform(controls, &Matrix4::new_scaling(factor))
+        .into_iter()
+        .map(|c| TrackControl {
+            width: c.width * factor,
+            ..c
+        })
+        .collect()
+}
+
+/// Blend between two tracks with the same number of controls, from `a` at `t = 0` to `b` at
+/// `t = 1`. Directions rotate from one to the other while their lengths are interpolated, and
+/// banking is interpolated without unwrapping so that full rolls are kept.
+pub fn morph(a: &[TrackControl], b: &[TrackControl], t: f32) -> Result<Vec<TrackControl>> {
+    if a.len() != b.len() {
+        return Err(format_err!(
+            "Cannot morph between tracks of {} and {} controls",
+            a.len(),
+            b.len()
+        ));
+    }
+
+    Ok(a.iter()
+        .zip(b)
+        .map(|(a, b)| {
+            let magnitude = lerp(a.direction.magnitude(), b.direction.magnitude(), t);
+            // Antiparallel directions have no unique rotation between them, so snap halfway
+            let direction = match UnitQuaternion::rotation_between(&a.direction, &b.direction) {
+                Some(r) => UnitQuaternion::identity().slerp(&r, t) * a.direction.normalize(),
+                None if t < 0.5 => a.direction.normalize(),
+                None => b.direction.normalize(),
+            };
+            TrackControl {
+                position: Point3::from(a.position.coords.lerp(&b.position.coords, t)),
+                direction: direction * magnitude,
+                angle: lerp(a.angle, b.angle, t),
+                full_roll: if t < 0.5 { a.full_roll } else { b.full_roll },
+                width: lerp(a.width, b.width, t),
+                in_length: lerp(a.in_length, b.in_length, t),
+                out_length: lerp(a.out_length, b.out_length, t),
+                weight: lerp(a.weight, b.weight, t),
+                color: [0, 1, 2].map(|c| lerp(a.color[c], b.color[c], t)),
+            }
+        })
+        .collect())
+}
+
+/// Reflect a track across the plane through `plane_point` with normal `plane_normal`.
+///
+/// Reflection flips handedness, so banking is recomputed to keep the track surface facing the
+/// mirrored up direction at each control, and rolls between controls turn the opposite way.
+/// Mirroring across a horizontal plane therefore turns the track upside down.
+/// The mirrored track still runs in the same order, which for a closed circuit means it now
+/// winds the opposite way around; use `reverse` as well to keep the winding.
+pub fn mirror(
+    controls: &[TrackControl],
+    plane_normal: Vector3<f32>,
+    plane_point: Point3<f32>,
+) -> Vec<TrackControl> {
+    let n = plane_normal.normalize();
+    let reflect = |v: Vector3<f32>| v - n * (2. * v.dot(&n));
+    let mut mirrored: Vec<TrackControl> = controls
+        .iter()
+        .map(|c| TrackControl {
+            position: plane_point + reflect(c.position - plane_point),
+            direction: reflect(c.direction),
+            ..*c
+        })
+        .collect();
+
+    let ups: Vec<_> = controls
+        .iter()
+        .map(|c| reflect(up_vector(&c.direction, c.angle)))
+        .collect();
+    let rolls = negated_rolls(controls.iter());
+    match_banking(&mut mirrored, &ups, &rolls);
+    mirrored
+}
+
+/// Reverse the direction of travel along a track. Banking is recomputed so that the track surface
+/// faces the same way at each control, and each `full_roll` stays with its segment.
+pub fn reverse(controls: &[TrackControl]) -> Vec<TrackControl> {
+    let mut reversed: Vec<TrackControl> = controls
+        .iter()
+        .rev()
+        .map(|c| TrackControl {
+            direction: -c.direction,
+            in_length: c.out_length,
+            out_length: c.in_length,
+            ..*c
+        })
+        .collect();
+
+    // A segment's full_roll lives on its end control, which is now its start
+    for idx in (1..reversed.len()).rev() {
+        reversed[idx].full_roll = reversed[idx - 1].full_roll;
+    }
+    if let Some(first) = reversed.first_mut() {
+        first.full_roll = false;
+    }
+
+    let ups: Vec<_> = controls
+        .iter()
+        .rev()
+        .map(|c| up_vector(&c.direction, c.angle))
+        .collect();
+    let rolls = negated_rolls(controls.iter().rev());
+    match_banking(&mut reversed, &ups, &rolls);
+    reversed
+}
+
+/// Roll from each control to the next, negated; the first entry is unused
+fn negated_rolls<'a>(controls: impl Iterator<Item = &'a TrackControl>) -> Vec<f32> {
+    let angles: Vec<f32> = controls.map(|c| c.angle).collect();
+    let mut rolls = vec![0.];
+    rolls.extend(angles.windows(2).map(|pair| pair[0] - pair[1]));
+    rolls
+}
+
+/// Distance within which the ends of a track are considered joined
+const CLOSED_EPSILON: f32 = 1e-4;
+
+/// Whether the last control of the track returns to the position of the first
+pub fn is_closed(controls: &[TrackControl]) -> bool {
+    match (controls.first(), controls.last()) {
+        (Some(first), Some(last)) if controls.len() > 2 => {
+            (first.position - last.position).magnitude() <= CLOSED_EPSILON
+        }
+        _ => false,
+    }
+}
+
+/// Soften changes in banking by averaging each control's angle with its neighbors, `passes`
+/// times. The ends of an open track are clamped, while a closed track wraps around.
+pub fn smooth_banking(controls: &mut [TrackControl], passes: usize) {
+    let n = controls.len();
+    if n < 3 {
+        return;
+    }
+
+    // Unwrap the angles so that averaging follows the roll actually taken between controls
+    let mut angles = vec![controls[0].angle];
+    for idx in 1..n {
+        let (prev, control) = (&controls[idx - 1], &controls[idx]);
+        let to = match control.full_roll {
+            true => control.angle,
+            false => shortest_arc(prev.angle, control.angle),
+        };
+        angles.push(angles[idx - 1] + to - prev.angle);
+    }
+
+    // On a closed track the last control is the first, plus however many turns the track rolls
+    let closed = is_closed(controls);
+    let turns = angles[n - 1] - angles[0];
+    let count = if closed { n - 1 } else { n };
+    for _ in 0..passes {
+        let prev = angles.clone();
+        let neighbor = |idx: isize| match closed {
+            true if idx < 0 => prev[(idx + count as isize) as usize] - turns,
+            true if idx >= count as isize => prev[idx as usize - count] + turns,
+            _ => prev[idx.clamp(0, n as isize - 1) as usize],
+        };
+        for idx in 0..count {
+            let i = idx as isize;
+            angles[idx] = (neighbor(i - 1) + 2. * prev[idx] + neighbor(i + 1)) / 4.;
+        }
+        if closed {
+            angles[n - 1] = angles[0] + turns;
+        }
+    }
+
+    for (control, angle) in controls.iter_mut().zip(angles) {
+        control.angle = angle;
+    }
+}
+
+/// Points spaced roughly `resolution` apart along the track, with the distance traveled to each
+fn polyline(controls: &[TrackControl], resolution: f32) -> Vec<(f32, Point3<f32>)> {
+    let mut points: Vec<(f32, Point3<f32>)> = Vec::new();
+    for sample in TrackFollower::new(controls, resolution) {
+        let distance = match points.last() {
+            Some((distance, last)) => distance + (sample.position - last).magnitude(),
+            None => 0.,
+        };
+        points.push((distance, sample.position));
+    }
+    points
+}
+
+/// Places where the track crosses itself when seen from above, as pairs of distances along the
+/// track, approximating it with points roughly `resolution` apart. Crossings more than
+/// `height_tol` apart vertically are bridges, and are not included.
+pub fn self_intersections(
+    controls: &[TrackControl],
+    resolution: f32,
+    height_tol: f32,
+) -> Vec<(f32, f32)> {
+    let points = polyline(controls, resolution);
+    let lines: Vec<_> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    let cross = |a: Vector2<f32>, b: Vector2<f32>| a.x * b.y - a.y * b.x;
+    let closed = is_closed(controls);
+
+    let mut crossings = Vec::new();
+    for (idx, &((d0, a0), (d1, a1))) in lines.iter().enumerate() {
+        for (jdx, &((e0, b0), (e1, b1))) in lines.iter().enumerate().skip(idx + 2) {
+            // Lines joined across the seam of a closed track are neighbors too
+            if closed && idx == 0 && jdx == lines.len() - 1 {
+                continue;
+            }
+
+            let r = a1.xz() - a0.xz();
+            let s = b1.xz() - b0.xz();
+            let denom = cross(r, s);
+            if denom.abs() <= f32::EPSILON {
+                continue;
+            }
+            let offset = b0.xz() - a0.xz();
+            let t = cross(offset, s) / denom;
+            let u = cross(offset, r) / denom;
+            if !(0. ..1.).contains(&t) || !(0. ..1.).contains(&u) {
+                continue;
+            }
+
+            let height_a = lerp(a0.y, a1.y, t);
+            let height_b = lerp(b0.y, b1.y, u);
+            if (height_a - height_b).abs() <= height_tol {
+                crossings.push((lerp(d0, d1, t), lerp(e0, e1, u)));
+            }
+        }
+    }
+    crossings
+}
+
+/// Total ascent and total descent along the track, sampled roughly `resolution` apart
+pub fn climb_descent(controls: &[TrackControl], resolution: f32) -> (f32, f32) {
+    let points = polyline(controls, resolution);
+    let mut climb = 0.;
+    let mut descent = 0.;
+    for pair in points.windows(2) {
+        let rise = pair[1].1.y - pair[0].1.y;
+        if rise > 0. {
+            climb += rise;
+        } else {
+            descent -= rise;
+        }
+    }
+    (climb, descent)
+}
+
+/// Lowest and highest point of the track, sampled roughly `resolution` apart as well as at each
+/// control. Zero for an empty track.
+pub fn elevation_extents(controls: &[TrackControl], resolution: f32) -> (f32, f32) {
+    let samples = polyline(controls, resolution).into_iter().map(|(_, p)| p.y);
+    let ends = controls.iter().map(|c| c.position.y);
+    samples
+        .chain(ends)
+        .fold(None, |extents: Option<(f32, f32)>, y| match extents {
+            Some((low, high)) => Some((low.min(y), high.max(y))),
+            None => Some((y, y)),
+        })
+        .unwrap_or((0., 0.))
+}
+
+/// How the ends of a track are treated by `smooth_c2`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum EndCondition {
+    /// Zero curvature at the ends
+    #[default]
+    Natural,
+    /// Keep the existing outgoing handle at the start and incoming handle at the end
+    Clamped,
+}
+
+/// Re-fit the direction of each control, keeping positions, so that the second derivative is
+/// continuous across every interior control, as with a cubic interpolating spline. Handles become
+/// symmetric.
+pub fn smooth_c2(controls: &mut [TrackControl], end: EndCondition) {
+    let n = controls.len();
+    if n < 2 {
+        return;
+    }
+    let p = |idx: usize| controls[idx].position.coords;
+
+    // Tridiagonal system in the tangents (three times each direction); `sub`, `diag` and `sup`
+    // are the coefficients of the previous, current and next tangents in each row
+    let mut sub = vec![1.; n];
+    let mut diag = vec![4.; n];
+    let mut sup = vec![1.; n];
+    let mut rhs: Vec<Vector3<f32>> = (0..n)
+        .map(|idx| (p((idx + 1).min(n - 1)) - p(idx.saturating_sub(1))) * 3.)
+        .collect();
+    match end {
+        EndCondition::Natural => {
+            diag[0] = 2.;
+            diag[n - 1] = 2.;
+        }
+        EndCondition::Clamped => {
+            diag[0] = 1.;
+            sup[0] = 0.;
+            rhs[0] = controls[0].direction * (controls[0].out_length * 3.);
+            diag[n - 1] = 1.;
+            sub[n - 1] = 0.;
+            rhs[n - 1] = controls[n - 1].direction * (controls[n - 1].in_length * 3.);
+        }
+    }
+
+    let tangents = solve_tridiagonal(&sub, &mut diag, &sup, rhs);
+    for (control, tangent) in controls.iter_mut().zip(tangents) {
+        control.direction = tangent / 3.;
+        control.in_length = 1.;
+        control.out_length = 1.;
+    }
+}
+
+/// Solve a tridiagonal system with the Thomas algorithm; `sub`, `diag` and `sup` are the
+/// coefficients of the previous, current and next unknowns in each row. `diag` is overwritten.
+fn solve_tridiagonal<T>(sub: &[f32], diag: &mut [f32], sup: &[f32], mut rhs: Vec<T>) -> Vec<T>
+where
+    T: Copy + Sub<Output = T> + Mul<f32, Output = T> + Div<f32, Output = T>,
+{
+    // Eliminate below the diagonal, then back substitute
+    let n = rhs.len();
+    for idx in 1..n {
+        let factor = sub[idx] / diag[idx - 1];
+        diag[idx] -= factor * sup[idx - 1];
+        rhs[idx] = rhs[idx] - rhs[idx - 1] * factor;
+    }
+    rhs[n - 1] = rhs[n - 1] / diag[n - 1];
+    for idx in (0..n - 1).rev() {
+        rhs[idx] = (rhs[idx] - rhs[idx + 1] * sup[idx]) / diag[idx];
+    }
+    rhs
+}
+
+/// Approximate the track by a polyline staying within `tolerance` of the curve, subdividing
+/// each segment only as far as needed. Every control lies exactly on the polyline.
+pub fn flatten(controls: &[TrackControl], tolerance: f32) -> Vec<Point3<f32>> {
+    let mut points: Vec<Point3<f32>> = controls.first().map(|c| c.position).into_iter().collect();
+    for pair in controls.windows(2) {
+        let cubic = [
+            pair[0].position,
+            pair[0].front_ctrlp(),
+            pair[1].back_ctrlp(),
+            pair[1].position,
+        ];
+        let weights = [1., pair[0].weight, pair[1].weight, 1.];
+        flatten_cubic(&cubic, &weights, tolerance, 0, &mut points);
+    }
+    points
+}
+
+/// Append the end of `cubic` to `points` if it is flat enough, otherwise split it in half.
+/// `weights` are those of the control points, for rational segments.
+fn flatten_cubic(
+    cubic: &Cubic,
+    weights: &[f32; 4],
+    tolerance: f32,
+    depth: usize,
+    points: &mut Vec<Point3<f32>>,
+) {
+    const MAX_DEPTH: usize = 16;
+    let [p0, p1, p2, p3] = *cubic;
+
+    // The curve lies within the hull of its control points, given positive weights
+    let flat = segment_distance(&p1, &p0, &p3).max(segment_distance(&p2, &p0, &p3)) <= tolerance;
+    if flat || depth >= MAX_DEPTH {
+        points.push(p3);
+        return;
+    }
+
+    // De Casteljau's algorithm, in homogeneous coordinates
+    let mid = |(a, wa): (Point3<f32>, f32), (b, wb): (Point3<f32>, f32)| {
+        let point = Point3::from((a.coords * wa + b.coords * wb) / (wa + wb));
+        (point, (wa + wb) / 2.)
+    };
+    let [w0, w1, w2, w3] = *weights;
+    let (p0, p1, p2, p3) = ((p0, w0), (p1, w1), (p2, w2), (p3, w3));
+    let (p01, p12, p23) = (mid(p0, p1), mid(p1, p2), mid(p2, p3));
+    let (p012, p123) = (mid(p01, p12), mid(p12, p23));
+    let center = mid(p012, p123);
+    for half in [[p0, p01, p012, center], [center, p123, p23, p3]] {
+        let cubic = half.map(|(point, _)| point);
+        let weights = half.map(|(_, weight)| weight);
+        flatten_cubic(&cubic, &weights, tolerance, depth + 1, points);
+    }
+}
+
+/// Distance from `point` to the line segment from `a` to `b`
+fn segment_distance(point: &Point3<f32>, a: &Point3<f32>, b: &Point3<f32>) -> f32 {
+    let line = b - a;
+    let length_sq = line.magnitude_squared();
+    let t = match length_sq > f32::EPSILON {
+        true => ((point - a).dot(&line) / length_sq).clamp(0., 1.),
+        false => 0.,
+    };
+    (point - (a + line * t)).magnitude()
+}
+
+/// Thin a polyline with the Ramer-Douglas-Peucker algorithm, dropping points which lie within
+/// `epsilon` of the simplified line. The first and last points are always kept.
+pub fn simplify(points: &[Point3<f32>], epsilon: f32) -> Vec<Point3<f32>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    // Split spans at their farthest point until every span is within epsilon
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut spans = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = spans.pop() {
+        let farthest = (first + 1..last)
+            .map(|idx| (idx, segment_distance(&points[idx], &points[first], &points[last])))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        if let Some((idx, distance)) = farthest {
+            if distance > epsilon {
+                keep[idx] = true;
+                spans.push((first, idx));
+                spans.push((idx, last));
+            }
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, keep)| keep.then_some(*point))
+        .collect()
+}
+
+/// Curvature comb of the track, sampled roughly `resolution` apart: a line from each sample
+/// pointing away from its center of curvature, `curvature * scale` long. The comb swaps sides
+/// wherever the track's turn changes direction.
+pub fn curvature_comb(
+    controls: &[TrackControl],
+    resolution: f32,
+    scale: f32,
+) -> Vec<(Point3<f32>, Point3<f32>)> {
+    TrackFollower::new(controls, resolution)
+        .map(|sample| {
+            let base = sample.index as usize;
+            let eval = SegmentEval::new(&controls[base], &controls[base + 1]);
+            let towards_center = eval.curvature_vector(sample.index.fract());
+            (sample.position, sample.position - towards_center * scale)
+        })
+        .collect()
+}
+
+/// Banking angle for a coordinated turn, at which the sum of gravity and the centripetal force
+/// points straight into the track surface, so that nothing slides sideways. Friction with the
+/// track surface is ignored.
+pub fn coordinated_bank_angle(curvature: f32, speed: f32, gravity: f32) -> f32 {
+    (speed * speed * curvature / gravity).atan()
+}
+
+/// Bank each control for a coordinated turn at `speed`, leaning into the sideways component of
+/// the track's curvature at that control
+pub fn auto_bank(controls: &mut [TrackControl], speed: f32, gravity: f32) {
+    let n = controls.len();
+    if n < 2 {
+        return;
+    }
+    for idx in 0..n {
+        // Measure at the start of the segment leaving each control, or the end of the last one
+        let (begin, i) = match idx + 1 < n {
+            true => (idx, 0.),
+            false => (idx - 1, 1.),
+        };
+        let eval = SegmentEval::new(&controls[begin], &controls[begin + 1]);
+        let towards_center = eval.curvature_vector(i);
+        let right = orientation(&controls[idx].direction, 0., &Vector3::x())
+            .transform_vector(&Vector3::z());
+        controls[idx].angle = coordinated_bank_angle(towards_center.dot(&right), speed, gravity);
+    }
+}
+
+/// Track through `points` with Kochanek-Bartels tangents, using the same tension, continuity and
+/// bias at every point. Banking angles are zero.
+pub fn from_tcb(
+    points: &[Point3<f32>],
+    tension: f32,
+    continuity: f32,
+    bias: f32,
+) -> Vec<TrackControl> {
+    from_tcb_per_control(points, &vec![(tension, continuity, bias); points.len()])
+}
+
+/// Track through `points` with Kochanek-Bartels tangents, given a `(tension, continuity, bias)`
+/// triple for each point. The ends are treated as if the track continued straight past them.
+/// Panics unless there are as many triples as points.
+///
+/// Both handles of a control share a direction, so where continuity is nonzero the incoming and
+/// outgoing tangents are averaged.
+pub fn from_tcb_per_control(
+    points: &[Point3<f32>],
+    params: &[(f32, f32, f32)],
+) -> Vec<TrackControl> {
+    assert_eq!(
+        params.len(),
+        points.len(),
+        "Expected a (tension, continuity, bias) triple for each point"
+    );
+    let n = points.len();
+    (0..n)
+        .map(|idx| {
+            let point = points[idx];
+            let before = match idx {
+                0 if n > 1 => point - (points[1] - point),
+                0 => point,
+                _ => points[idx - 1],
+            };
+            let after = match points.get(idx + 1) {
+                Some(after) => *after,
+                None => point + (point - before),
+            };
+            let (incoming, outgoing) = tcb_tangents(before, point, after, params[idx]);
+
+            // Hermite tangents are three times the length of Bezier handles
+            TrackControl::new(point, (incoming + outgoing) / 6., 0.)
+        })
+        .collect()
+}
+
+/// Incoming and outgoing Kochanek-Bartels tangents at `point`
+fn tcb_tangents(
+    before: Point3<f32>,
+    point: Point3<f32>,
+    after: Point3<f32>,
+    (tension, continuity, bias): (f32, f32, f32),
+) -> (Vector3<f32>, Vector3<f32>) {
+    let back = point - before;
+    let ahead = after - point;
+    let scale = (1. - tension) / 2.;
+    let incoming = back * (scale * (1. + bias) * (1. - continuity))
+        + ahead * (scale * (1. - bias) * (1. + continuity));
+    let outgoing = back * (scale * (1. + bias) * (1. + continuity))
+        + ahead * (scale * (1. - bias) * (1. - continuity));
+    (incoming, outgoing)
+}
+
+/// Exact circular arc of `radius` about `center` on the horizontal plane, starting at
+/// `start_angle` and turning through `sweep` radians. Angles are measured from +X toward +Z.
+/// The arc is split into segments of at most a quarter turn, each a rational cubic. Banking angles
+/// are zero.
+pub fn circular_arc(
+    center: Point3<f32>,
+    radius: f32,
+    start_angle: f32,
+    sweep: f32,
+) -> Vec<TrackControl> {
+    use core::f32::consts::FRAC_PI_2;
+    let segments = (sweep.abs() / FRAC_PI_2).ceil().max(1.) as usize;
+    let step = sweep / segments as f32;
+
+    // Degree elevation of the rational quadratic arc, whose middle weight is cos(step / 2)
+    let half_cos = (step / 2.).cos();
+    let weight = (1. + 2. * half_cos) / 3.;
+    let handle = 2. * radius * (step / 2.).sin() / (1. + 2. * half_cos);
+
+    (0..=segments)
+        .map(|idx| {
+            let angle = start_angle + step * idx as f32;
+            let (sin, cos) = angle.sin_cos();
+            let position = center + Vector3::new(cos, 0., sin) * radius;
+            let direction = Vector3::new(-sin, 0., cos) * handle;
+            TrackControl {
+                weight,
+                ..TrackControl::new(position, direction, 0.)
+            }
+        })
+        .collect()
+}
+
+/// Helix about a vertical axis through `center`, rising `pitch` per turn. See `helix_about`.
+pub fn helix(
+    center: Point3<f32>,
+    radius: f32,
+    pitch: f32,
+    turns: f32,
+    segments_per_turn: usize,
+) -> Vec<TrackControl> {
+    helix_about(center, Vector3::y(), radius, pitch, turns, segments_per_turn)
+}
+
+/// Helix about `axis` through `center`, advancing `pitch` along the axis per turn. Negative
+/// `turns` wind the other way. The track surface faces the axis, so it rolls once per turn as in
+/// a corkscrew; use `auto_bank` instead for a banked helix turn.
+pub fn helix_about(
+    center: Point3<f32>,
+    axis: Vector3<f32>,
+    radius: f32,
+    pitch: f32,
+    turns: f32,
+    segments_per_turn: usize,
+) -> Vec<TrackControl> {
+    use core::f32::consts::TAU;
+    let axis = axis.normalize();
+    let u = match axis.x.abs() < 0.9 {
+        true => (Vector3::x() - axis * axis.x).normalize(),
+        false => (Vector3::z() - axis * axis.z).normalize(),
+    };
+    let v = u.cross(&axis);
+
+    let segments = ((turns.abs() * segments_per_turn as f32).ceil() as usize).max(1);
+    let sweep = turns * TAU;
+    let rise = pitch * turns.abs();
+
+    // Handles of a circular arc are (4/3)tan(step/4) of the radius
+    let step = sweep / segments as f32;
+    let handle = match step.abs() > f32::EPSILON {
+        true => 4. / 3. * (step / 4.).tan() / step,
+        false => 1. / 3.,
+    };
+
+    let mut controls = Vec::with_capacity(segments + 1);
+    let mut ups = Vec::with_capacity(segments + 1);
+    for idx in 0..=segments {
+        let t = idx as f32 / segments as f32;
+        let (sin, cos) = (sweep * t).sin_cos();
+        let outward = u * cos + v * sin;
+        let position = center + outward * radius + axis * (rise * t);
+        let deriv = (v * cos - u * sin) * (radius * sweep) + axis * rise;
+        controls.push(TrackControl::new(position, deriv * (handle / segments as f32), 0.));
+        ups.push(-outward);
+    }
+    match_banking(&mut controls, &ups, &vec![0.; ups.len()]);
+    controls
+}
+
+/// Euler spiral of `length` on the horizontal plane, whose curvature changes linearly from
+/// `start_curvature` to `end_curvature`, approximated by `samples` cubic segments. It starts at
+/// the origin heading along +X; positive curvature turns toward +Z. Banking angles are zero.
+///
+/// Each segment matches the spiral's position and heading at both ends. For segments of length
+/// `h`, the position error is at most `h⁴ / 384` times the largest `sqrt(9κ²κ'² + κ⁶)` along the
+/// spiral, where `κ'` is the change in curvature per unit length. Curvature is less accurate, with
+/// error falling off with `h²`; easing from 0 to 0.1 over 30 units is within 0.011 of the spiral's
+/// curvature with 2 samples, and within 0.0011 with 8.
+pub fn clothoid_transition(
+    start_curvature: f32,
+    end_curvature: f32,
+    length: f32,
+    samples: usize,
+) -> Vec<TrackControl> {
+    const SUBSTEPS: usize = 16;
+    let samples = samples.max(1);
+    let rate = (end_curvature - start_curvature) / length;
+    let heading = |s: f32| start_curvature * s + rate * s * s / 2.;
+    let tangent = |s: f32| {
+        let (sin, cos) = heading(s).sin_cos();
+        Vector3::new(cos, 0., sin)
+    };
+
+    // Integrate the tangent with Simpson's rule
+    let step = length / samples as f32;
+    let substep = step / SUBSTEPS as f32;
+    let mut position = Point3::origin();
+    let mut controls = vec![TrackControl::new(position, tangent(0.) * (step / 3.), 0.)];
+    for idx in 0..samples {
+        let start = step * idx as f32;
+        for sub in 0..SUBSTEPS {
+            let s = start + substep * sub as f32;
+            let sum = tangent(s) + tangent(s + substep / 2.) * 4. + tangent(s + substep);
+            position += sum * (substep / 6.);
+        }
+        let end = start + step;
+        controls.push(TrackControl::new(position, tangent(end) * (step / 3.), 0.));
+    }
+    controls
+}
+
+/// Split the segment from `begin` to `end` at `t`, returning the new ends and the control
+/// between them, so that `begin, middle, end` trace the same curve. Position and derivative are
+/// exact for polynomial segments; rational ones (weights other than 1) are split as if they were
+/// polynomial. The middle control's banking is that of the segment at `t`.
+pub fn split_segment(
+    begin: &TrackControl,
+    end: &TrackControl,
+    t: f32,
+) -> (TrackControl, TrackControl, TrackControl) {
+    // De Casteljau's algorithm
+    let (p0, p1, p2, p3) = (
+        begin.position,
+        begin.front_ctrlp(),
+        end.back_ctrlp(),
+        end.position,
+    );
+    let at = |a: Point3<f32>, b: Point3<f32>| a + (b - a) * t;
+    let (p01, p12, p23) = (at(p0, p1), at(p1, p2), at(p2, p3));
+    let (p012, p123) = (at(p01, p12), at(p12, p23));
+    let center = at(p012, p123);
+
+    let direction = p123 - center;
+    let in_length = match direction.magnitude() {
+        m if m > 0. => (center - p012).magnitude() / m,
+        _ => 1.,
+    };
+    let middle = TrackControl {
+        position: center,
+        direction,
+        angle: bank_angle(begin, end, t, Easing::default()),
+        full_roll: end.full_roll,
+        width: lerp(begin.width, end.width, t),
+        in_length,
+        out_length: 1.,
+        weight: 1.,
+        color: color(begin, end, t, Easing::default()),
+    };
+    let begin = TrackControl {
+        out_length: begin.out_length * t,
+        ..*begin
+    };
+    let end = TrackControl {
+        in_length: end.in_length * (1. - t),
+        ..*end
+    };
+    (begin, middle, end)
+}
+
+/// Split a track at index `i` into the part before and the part after, which share the control
+/// at `i`. Returns `None` if `i` is past either end.
+pub fn split(controls: &[TrackControl], i: f32) -> Option<(Vec<TrackControl>, Vec<TrackControl>)> {
+    use core::convert::TryInto;
+    let base: usize = (i as i64).try_into().ok()?;
+    let t = i.fract();
+    if t == 0. && base < controls.len() {
+        return Some((controls[..=base].to_vec(), controls[base..].to_vec()));
+    }
+    let (begin, end) = (controls.get(base)?, controls.get(base + 1)?);
+    let (begin, middle, end) = split_segment(begin, end, t);
+
+    let mut before = controls[..base].to_vec();
+    before.extend_from_slice(&[begin, middle]);
+    let mut after = vec![middle, end];
+    after.extend_from_slice(&controls[base + 2..]);
+    Some((before, after))
+}
+
+/// Join two tracks end to end. Where `a` ends at the start of `b`, the shared control takes its
+/// incoming handle from `a` and everything else from `b`; the handles stay collinear only if the
+/// tracks already meet smoothly. Otherwise a straight-through segment joins them.
+pub fn concat(a: &[TrackControl], b: &[TrackControl]) -> Vec<TrackControl> {
+    let mut joined = a.to_vec();
+    match (joined.last_mut(), b.first()) {
+        (Some(last), Some(first))
+            if (last.position - first.position).magnitude() <= CLOSED_EPSILON =>
+        {
+            let incoming = last.direction * last.in_length;
+            let in_length = match first.direction.magnitude() {
+                m if m > 0. => incoming.magnitude() / m,
+                _ => 1.,
+            };
+            *last = TrackControl {
+                in_length,
+                ..*first
+            };
+            joined.extend_from_slice(&b[1..]);
+        }
+        _ => joined.extend_from_slice(b),
+    }
+    joined
+}
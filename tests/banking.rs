@@ -0,0 +1,118 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, TrackControl};
+
+/// Controls along a line on x with the given banking angles, ending where they began if `closed`
+fn banked(angles: &[f32], closed: bool) -> Vec<TrackControl> {
+    let n = angles.len();
+    angles
+        .iter()
+        .enumerate()
+        .map(|(idx, &angle)| {
+            let x = match closed && idx == n - 1 {
+                true => 0.,
+                false => idx as f32 * 10.,
+            };
+            TrackControl::new(Point3::new(x, 0., 0.), Vector3::x() * 10., angle)
+        })
+        .collect()
+}
+
+fn angles(controls: &[TrackControl]) -> Vec<f32> {
+    controls.iter().map(|c| c.angle).collect()
+}
+
+#[test]
+fn smoothing_spreads_a_spike() {
+    let mut controls = banked(&[0., 0., 1., 0., 0.], false);
+    track::smooth_banking(&mut controls, 1);
+    assert_eq!(angles(&controls), [0., 0.25, 0.5, 0.25, 0.]);
+
+    // Further passes keep flattening it out
+    track::smooth_banking(&mut controls, 4);
+    let after = angles(&controls);
+    assert!(after[2] < 0.5 && after[0] > 0.);
+    for pair in after.windows(2) {
+        assert!((pair[1] - pair[0]).abs() < 0.25);
+    }
+}
+
+#[test]
+fn smoothing_follows_the_short_way_round() {
+    // Nearly upside down either way; averaging the raw numbers would flip the middle upright
+    let mut controls = banked(&[3., -3., 3.], false);
+    track::smooth_banking(&mut controls, 1);
+    let middle = controls[1].angle;
+    assert!(middle.cos() < -0.99);
+}
+
+#[test]
+fn smoothing_wraps_closed_tracks() {
+    let mut controls = banked(&[0., 0.8, 0., 0., 0.], true);
+    assert!(track::is_closed(&controls));
+    track::smooth_banking(&mut controls, 1);
+    assert_eq!(angles(&controls), [0.2, 0.4, 0.2, 0., 0.2]);
+
+    // The start is averaged with the control before the seam, not clamped
+    let mut controls = banked(&[0., 0., 0., 0.8, 0.], true);
+    track::smooth_banking(&mut controls, 1);
+    assert_eq!(controls[0].angle, 0.2);
+}
+
+#[test]
+fn coordinated_bank_angle_balances_gravity() {
+    // Centripetal acceleration equal to gravity leans at 45 degrees
+    let radius = 20.;
+    let speed = (radius * 9.8f32).sqrt();
+    let angle = track::coordinated_bank_angle(1. / radius, speed, 9.8);
+    assert!((angle - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+    assert_eq!(track::coordinated_bank_angle(0., speed, 9.8), 0.);
+}
+
+/// The push of the track surface, against gravity and around the turn, is along its up vector at
+/// each control
+fn assert_coordinated(controls: &[TrackControl], speed: f32, gravity: f32) {
+    for (idx, control) in controls.iter().enumerate() {
+        let (begin, i) = match idx + 1 < controls.len() {
+            true => (idx, 0.),
+            false => (idx - 1, 1.),
+        };
+        let eval = track::SegmentEval::new(&controls[begin], &controls[begin + 1]);
+        let push = eval.curvature_vector(i) * speed * speed + Vector3::y() * gravity;
+        let sample = track::sample(&controls[begin], &controls[begin + 1], i);
+        assert!((sample.angle - control.angle).abs() < 1e-5);
+        assert!(push.normalize().dot(&sample.right()).abs() < 1e-3);
+        assert!(push.dot(&sample.up()) > 0.);
+    }
+}
+
+#[test]
+fn auto_bank_leans_into_turns() {
+    // Flat turns to the right then the left, with a straight between
+    let mut controls = vec![
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(20., 0., 20.), Vector3::new(0., 0., 10.), 0.),
+        TrackControl::new(Point3::new(20., 0., 40.), Vector3::new(0., 0., 10.), 0.),
+        TrackControl::new(Point3::new(40., 0., 60.), Vector3::new(10., 0., 0.), 0.),
+    ];
+    let (speed, gravity) = (15., 9.8);
+    track::auto_bank(&mut controls, speed, gravity);
+    assert!(controls[0].angle.abs() > 0.1);
+    assert!(controls[3].angle.abs() > 0.1);
+    assert!(controls[0].angle * controls[3].angle < 0.);
+    assert_coordinated(&controls, speed, gravity);
+}
+
+#[test]
+fn auto_bank_heading_back_along_x() {
+    // Controls pointing straight down -x, where the track's frame is a half turn from the default
+    let mut controls = vec![
+        TrackControl::new(Point3::origin(), Vector3::new(-10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(-20., 0., 20.), Vector3::new(0., 0., 10.), 0.),
+        TrackControl::new(Point3::new(-40., 0., 40.), Vector3::new(-10., 0., 0.), 0.),
+    ];
+    let (speed, gravity) = (15., 9.8);
+    track::auto_bank(&mut controls, speed, gravity);
+    assert!(controls.iter().all(|c| c.angle.is_finite()));
+    assert!(controls[0].angle.abs() > 0.1 && controls[2].angle.abs() > 0.1);
+    assert_coordinated(&controls, speed, gravity);
+}
@@ -0,0 +1,62 @@
+use nalgebra::{Matrix4, Point3, Vector3};
+use twisty_beziers::camera::ChaseCamera;
+use twisty_beziers::track::{self, TrackControl};
+
+#[test]
+fn chase_camera_settles_on_target() {
+    // Banked a quarter turn, so the target's up is sideways
+    let controls = [
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 1.5),
+        TrackControl::new(Point3::new(30., 0., 0.), Vector3::new(10., 0., 0.), 1.5),
+    ];
+    let target = track::sample_collection(&controls, 0.5).unwrap();
+    let quat = target.quaternion(&Vector3::x_axis());
+
+    let mut camera = ChaseCamera::new(Matrix4::identity());
+    let start = target.position.coords.magnitude();
+    let mut view = camera.transform();
+    let mut last_distance = f32::INFINITY;
+    for step in 1..=200 {
+        view = camera.update(&target, 4., 1. / 60.);
+        let distance = (view.transform_point(&Point3::origin()) - target.position).magnitude();
+
+        // Critically damped from rest, it closes in as (1 + wt) e^-wt and never overshoots
+        let wt = 4. * step as f32 / 60.;
+        assert!((distance - start * (1. + wt) * (-wt).exp()).abs() < 1e-3);
+        assert!(distance <= last_distance);
+        last_distance = distance;
+    }
+
+    let forward = view.transform_vector(&-Vector3::z());
+    let up = view.transform_vector(&Vector3::y());
+    assert!(last_distance < 0.01);
+    assert!((forward - quat * Vector3::x()).magnitude() < 1e-3);
+    assert!((up - quat * Vector3::y()).magnitude() < 1e-3);
+}
+
+#[test]
+fn chase_camera_keeps_its_velocity() {
+    let controls = [
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(30., 0., 0.), Vector3::new(10., 0., 0.), 0.),
+    ];
+    let start = track::sample_collection(&controls, 0.).unwrap();
+    let end = track::sample_collection(&controls, 0.9).unwrap();
+    let eye = |camera: &ChaseCamera| camera.transform().transform_point(&Point3::origin());
+
+    // One long step lands where many short ones do
+    let mut coarse = ChaseCamera::new(Matrix4::identity());
+    let mut fine = coarse;
+    coarse.update(&end, 3., 0.5);
+    for _ in 0..50 {
+        fine.update(&end, 3., 0.01);
+    }
+    assert!((eye(&coarse) - eye(&fine)).magnitude() < 1e-3);
+
+    // Once moving, the camera carries on past where it stands before turning back
+    let mut camera = ChaseCamera::new(Matrix4::identity());
+    camera.update(&end, 3., 0.5);
+    let before = eye(&camera).x;
+    camera.update(&start, 3., 0.05);
+    assert!(eye(&camera).x > before);
+}
@@ -0,0 +1,32 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::mesh;
+use twisty_beziers::track::TrackControl;
+
+fn track(len: usize) -> Vec<TrackControl> {
+    (0..len)
+        .map(|i| {
+            let position = Point3::new(i as f32 * 10., (i as f32).sin() * 4., 0.);
+            TrackControl::new(position, Vector3::new(5., 0., 2.), 0.)
+        })
+        .collect()
+}
+
+#[test]
+fn center_line_indices_in_bounds() {
+    for len in 0..6 {
+        for &resolution in &[0.1, 0.5, 3., 100.] {
+            let (vertices, indices) = mesh::track_center_line(&track(len), resolution, [1.; 3]);
+            assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+            assert_eq!(indices.len(), vertices.len().saturating_sub(1) * 2);
+        }
+    }
+}
+
+#[test]
+fn center_line_segments_are_consecutive() {
+    let (vertices, indices) = mesh::track_center_line(&track(4), 0.5, [1.; 3]);
+    for (n, pair) in indices.chunks_exact(2).enumerate() {
+        assert_eq!(pair, [n as u16, n as u16 + 1]);
+    }
+    assert_eq!(indices.last().copied(), Some(vertices.len() as u16 - 1));
+}
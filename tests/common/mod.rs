@@ -0,0 +1,10 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::TrackControl;
+
+/// A single straight segment along x, 30 units long
+pub fn straight() -> Vec<TrackControl> {
+    vec![
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(30., 0., 0.), Vector3::new(10., 0., 0.), 0.),
+    ]
+}
@@ -0,0 +1,173 @@
+use anyhow::{format_err, Result};
+use twisty_beziers::controls::{
+    AxisDeadzone, AxisMap, Blend, ClampCircle, Curve, Deadzone, Invert, Merge, Recorder, Remap,
+    Replay, ResponseCurve, Scripted, Smoothed, TwoAxisControls,
+};
+
+/// A source that always reads the same axes
+fn constant(axes: (f32, f32)) -> Scripted {
+    Scripted::new(move |_| axes)
+}
+
+/// A source whose device is always unavailable
+struct Unplugged;
+
+impl TwoAxisControls for Unplugged {
+    fn axes(&mut self) -> Result<(f32, f32)> {
+        Err(format_err!("Unplugged"))
+    }
+}
+
+fn close((x, y): (f32, f32), (ex, ey): (f32, f32)) -> bool {
+    (x - ex).abs() < 1e-5 && (y - ey).abs() < 1e-5
+}
+
+#[test]
+fn deadzone_rescales_past_the_radius() {
+    let axes = |input| Deadzone::new(constant(input), 0.2).axes().unwrap();
+    assert_eq!(axes((0.1, -0.1)), (0., 0.));
+    assert!(close(axes((0.6, 0.)), (0.5, 0.)));
+    assert!(close(axes((-1., 0.)), (-1., 0.)));
+
+    // Full deflection on a diagonal still reaches the rim, in the same direction
+    assert!(close(axes((0.6, 0.8)), (0.6, 0.8)));
+}
+
+#[test]
+#[should_panic]
+fn deadzone_rejects_full_radius() {
+    let _ = Deadzone::new(constant((0., 0.)), 1.);
+}
+
+#[test]
+fn axis_deadzone_rescales_each_axis() {
+    let mut controls = AxisDeadzone::new(constant((0.1, -0.6)), 0.2, 0.2);
+    assert!(close(controls.axes().unwrap(), (0., -0.5)));
+    let mut controls = AxisDeadzone::new(constant((1., -1.)), 0.2, 0.5);
+    assert!(close(controls.axes().unwrap(), (1., -1.)));
+}
+
+#[test]
+fn invert_flips_the_chosen_axes() {
+    let mut controls = Invert::new(constant((0.3, -0.4)), true, false);
+    assert_eq!(controls.axes().unwrap(), (-0.3, -0.4));
+    let mut controls = Invert::new(constant((0.3, -0.4)), false, true);
+    assert_eq!(controls.axes().unwrap(), (0.3, 0.4));
+}
+
+#[test]
+fn smoothing_converges_on_a_step() {
+    let mut steps = 0;
+    let step = Scripted::new(move |_| {
+        steps += 1;
+        match steps > 1 {
+            true => (1., -1.),
+            false => (0., 0.),
+        }
+    });
+    let alpha = 0.3;
+    let mut controls = Smoothed::new(step, alpha);
+
+    // The first sample passes through, then the error shrinks by 1 - alpha per sample
+    assert_eq!(controls.axes().unwrap(), (0., 0.));
+    for n in 1..=30 {
+        let (x, y) = controls.axes().unwrap();
+        let error = (1. - alpha).powi(n);
+        assert!((1. - x - error).abs() < 1e-5 && (1. + y - error).abs() < 1e-5);
+    }
+
+    // Resetting drops the filtered value, so the next sample passes through
+    controls.reset().unwrap();
+    assert_eq!(controls.axes().unwrap(), (1., -1.));
+}
+
+#[test]
+fn response_curve_shapes_and_clamps() {
+    let mut controls = ResponseCurve::new(
+        constant((0.5, -0.9)),
+        Curve::new(2., 2.),
+        Curve::new(2., 2.),
+    );
+    assert!(close(controls.axes().unwrap(), (0.5, -1.)));
+    let mut controls =
+        ResponseCurve::new(constant((0.5, -0.9)), Curve::default(), Curve::default());
+    assert!(close(controls.axes().unwrap(), (0.5, -0.9)));
+}
+
+#[test]
+fn blend_weights_and_falls_back() {
+    let mut controls = Blend::new(constant((1., 0.)), constant((0., 1.)), 0.25);
+    assert!(close(controls.axes().unwrap(), (0.75, 0.25)));
+    let mut controls = Blend::new(constant((1., 0.)), Unplugged, 0.25);
+    assert_eq!(controls.axes().unwrap(), (1., 0.));
+    let mut controls = Blend::new(Unplugged, constant((0., 1.)), 0.25);
+    assert_eq!(controls.axes().unwrap(), (0., 1.));
+    assert!(Blend::new(Unplugged, Unplugged, 0.5).axes().is_err());
+}
+
+#[test]
+fn clamp_circle_keeps_direction() {
+    let mut controls = ClampCircle::new(constant((3., 4.)));
+    assert!(close(controls.axes().unwrap(), (0.6, 0.8)));
+    let mut controls = ClampCircle::new(constant((1., 1.)));
+    let (x, y) = controls.axes().unwrap();
+    assert!((x.hypot(y) - 1.).abs() < 1e-5 && x == y);
+    let mut controls = ClampCircle::new(constant((0.3, -0.4)));
+    assert_eq!(controls.axes().unwrap(), (0.3, -0.4));
+}
+
+#[test]
+fn remap_swaps_before_inverting() {
+    let map = AxisMap {
+        swap_xy: true,
+        invert_x: true,
+        invert_y: false,
+    };
+    let mut controls = Remap::new(constant((0.2, 0.7)), map);
+    assert_eq!(controls.axes().unwrap(), (-0.7, 0.2));
+    let mut controls = Remap::new(constant((0.2, 0.7)), AxisMap::default());
+    assert_eq!(controls.axes().unwrap(), (0.2, 0.7));
+}
+
+#[test]
+fn merge_takes_the_strongest() {
+    let mut controls = Merge::new(vec![
+        Box::new(constant((0.2, 0.))),
+        Box::new(Unplugged),
+        Box::new(constant((0., -0.9))),
+    ]);
+    assert_eq!(controls.axes().unwrap(), (0., -0.9));
+    let mut controls = Merge::new(vec![Box::new(Unplugged)]);
+    assert_eq!(controls.axes().unwrap(), (0., 0.));
+}
+
+#[test]
+fn scripted_keyframes_interpolate_and_hold() {
+    let keyframes = vec![(0., (0., 0.)), (1., (1., -1.))];
+    let mut controls = Scripted::keyframes(keyframes).fixed_step(0.25);
+    let samples: Vec<_> = (0..6).map(|_| controls.axes().unwrap()).collect();
+    let expected = [0., 0.25, 0.5, 0.75, 1., 1.];
+    for (sample, &e) in samples.iter().zip(&expected) {
+        assert!(close(*sample, (e, -e)));
+    }
+}
+
+#[test]
+fn recording_replays_from_csv() {
+    let script = Scripted::new(|t| (t.sin(), t * 0.1)).fixed_step(0.1);
+    let mut recorder = Recorder::new(script);
+    let samples: Vec<_> = (0..10).map(|_| recorder.axes().unwrap()).collect();
+    let logged: Vec<_> = recorder.log().iter().map(|&(_, axes)| axes).collect();
+    assert_eq!(logged, samples);
+
+    let mut csv = Vec::new();
+    recorder.write_csv(&mut csv).unwrap();
+    let replay = Replay::from_csv(&csv[..]).unwrap();
+    assert_eq!(replay.log(), recorder.log());
+    let &(end, last) = replay.log().last().unwrap();
+    assert_eq!(replay.sample_at(end + 1.), last);
+
+    // Malformed rows are rejected
+    assert!(Replay::from_csv(&b"time,x,y\n0,1\n"[..]).is_err());
+    assert!(Replay::from_csv(&b"time,x,y\n0,one,2\n"[..]).is_err());
+}
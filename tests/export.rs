@@ -0,0 +1,42 @@
+mod common;
+
+use common::straight;
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track;
+
+#[test]
+fn bake_transforms_at_constant_speed() {
+    // 1.5 units a frame, so 20 frames after the first
+    let transforms = track::bake_transforms(&straight(), 10., 15.);
+    assert_eq!(transforms.len(), 21);
+    for (frame, transform) in transforms.iter().enumerate() {
+        let position = transform.transform_point(&Point3::origin());
+        assert!((position - Point3::new(frame as f32 * 1.5, 0., 0.)).magnitude() < 2e-2);
+        assert!((transform.transform_vector(&Vector3::x()) - Vector3::x()).magnitude() < 1e-4);
+        assert!((transform.transform_vector(&Vector3::y()) - Vector3::y()).magnitude() < 1e-4);
+    }
+    assert!(track::bake_transforms(&straight(), 0., 15.).is_empty());
+    assert!(track::bake_transforms(&straight()[..1], 10., 15.).is_empty());
+}
+
+#[test]
+fn transforms_json_lists_frames() {
+    let transforms = track::bake_transforms(&straight(), 10., 15.);
+    let mut json = Vec::new();
+    track::write_transforms_json(&transforms, 10., &mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+
+    let frames = json
+        .trim()
+        .strip_prefix(r#"{"fps":10,"frames":[["#)
+        .and_then(|rest| rest.strip_suffix("]]}"))
+        .unwrap();
+    let frames: Vec<Vec<f32>> = frames
+        .split("],[")
+        .map(|frame| frame.split(',').map(|v| v.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(frames.len(), transforms.len());
+    for (frame, transform) in frames.iter().zip(&transforms) {
+        assert_eq!(frame[..], transform.as_slice()[..]);
+    }
+}
@@ -0,0 +1,61 @@
+use nalgebra::Point3;
+use twisty_beziers::track::{self, TrackControl};
+
+/// Points along a gentle S-bend rising along x
+fn s_bend(n: usize) -> Vec<Point3<f32>> {
+    (0..n)
+        .map(|idx| {
+            let x = idx as f32 * 60. / (n - 1) as f32;
+            Point3::new(x, x * 0.1, (x / 10.).sin() * 5.)
+        })
+        .collect()
+}
+
+/// Distance from `point` to the track, measured against a fine polyline through it
+fn distance_to_track(controls: &[TrackControl], point: Point3<f32>) -> f32 {
+    let steps = (controls.len() - 1) * 500;
+    let polyline: Vec<_> = (0..steps)
+        .filter_map(|k| track::sample_collection(controls, k as f32 / 500.))
+        .map(|sample| sample.position)
+        .chain(controls.last().map(|c| c.position))
+        .collect();
+    polyline
+        .windows(2)
+        .map(|pair| {
+            let line = pair[1] - pair[0];
+            let t = ((point - pair[0]).dot(&line) / line.magnitude_squared()).clamp(0., 1.);
+            (point - (pair[0] + line * t)).magnitude()
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+#[test]
+fn fit_stays_within_max_error() {
+    let points = s_bend(200);
+    for &max_error in &[0.5, 0.1, 0.02] {
+        let controls = track::fit(&points, max_error);
+        assert!(controls.len() >= 2 && controls.len() < points.len() / 4);
+        assert_eq!(controls[0].position, points[0]);
+        assert_eq!(controls.last().unwrap().position, *points.last().unwrap());
+        for point in &points {
+            assert!(distance_to_track(&controls, *point) <= max_error * 1.01);
+        }
+    }
+}
+
+#[test]
+fn simplify_noisy_straight_line() {
+    // Alternating jitter of 0.05 off a line along x
+    let points: Vec<_> = (0..50)
+        .map(|idx| {
+            let noise = if idx % 2 == 0 { 0.05 } else { -0.05 };
+            Point3::new(idx as f32, noise, -noise)
+        })
+        .collect();
+    // The endpoints are jittered too, so points can sit up to 0.1 * sqrt(2) off their chord
+    let simplified = track::simplify(&points, 0.2);
+    assert_eq!(simplified, [points[0], points[49]]);
+
+    // Tighter than the noise keeps the jitter
+    assert!(track::simplify(&points, 0.01).len() > 2);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::straight;
+use twisty_beziers::track::{StepResult, TrackFollower};
+
+#[test]
+fn step_holds_at_the_end() {
+    let controls = straight();
+    let mut follower = TrackFollower::new(&controls, 4.);
+    let mut samples = 0;
+    while let Some(StepResult::Sample(_)) = follower.step() {
+        samples += 1;
+    }
+    assert_eq!(samples, TrackFollower::new(&controls, 4.).count());
+
+    // Held at the end for as long as it's stepped
+    for _ in 0..3 {
+        match follower.step() {
+            Some(StepResult::ReachedEnd(sample)) => {
+                assert!((sample.position - controls[1].position).magnitude() < 1e-4);
+            }
+            other => panic!("Expected the end, got {:?}", other),
+        }
+    }
+    assert_eq!(follower.i, 1.);
+}
+
+#[test]
+fn step_backward_from_the_start() {
+    let controls = straight();
+    let mut follower = TrackFollower::new(&controls, -1.);
+    match follower.step() {
+        Some(StepResult::ReachedEnd(sample)) => assert_eq!(sample.position, controls[0].position),
+        other => panic!("Expected the start, got {:?}", other),
+    }
+
+    // Looping followers wrap instead
+    let mut follower = TrackFollower::new(&controls, -1.).with_looping();
+    assert!(matches!(follower.step(), Some(StepResult::Sample(_))));
+    assert!(TrackFollower::new(&controls[..1], 1.).step().is_none());
+}
@@ -0,0 +1,109 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, ArcLengthTable, SegmentEval, TrackControl};
+
+/// Evaluate every segment of the track at `steps + 1` evenly spaced parameters
+fn dense(controls: &[TrackControl], steps: usize) -> Vec<(SegmentEval, f32)> {
+    controls
+        .windows(2)
+        .flat_map(|pair| {
+            let eval = SegmentEval::new(&pair[0], &pair[1]);
+            (0..=steps).map(move |k| (eval, k as f32 / steps as f32))
+        })
+        .collect()
+}
+
+#[test]
+fn circular_arc_is_exact() {
+    let center = Point3::new(5., 2., -3.);
+    let radius = 40.;
+    // At most a quarter turn per segment
+    for &(sweep, segments) in &[(0.3, 1), (-1.5, 1), (4., 3)] {
+        let controls = track::circular_arc(center, radius, 0.5, sweep);
+        assert_eq!(controls.len(), segments + 1);
+        let end = 0.5 + sweep;
+        let expected = center + Vector3::new(end.cos(), 0., end.sin()) * radius;
+        assert!((controls.last().unwrap().position - expected).magnitude() < 1e-3);
+
+        for (eval, i) in dense(&controls, 50) {
+            let offset = eval.eval(i) - center;
+            assert!((offset.magnitude() - radius).abs() < 1e-3);
+            assert!(offset.y.abs() < 1e-5);
+            assert!((eval.curvature(i) - 1. / radius).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn helix_winds_round_its_axis() {
+    let center = Point3::new(0., 10., 0.);
+    let (radius, pitch, turns) = (15., 8., 2.5);
+    let controls = track::helix(center, radius, pitch, turns, 8);
+    assert_eq!(controls.len(), 21);
+    let end = controls.last().unwrap().position;
+    assert!((end.y - (center.y + pitch * turns)).abs() < 1e-3);
+
+    // Eight segments a turn approximate a circle within a fraction of a percent
+    for (eval, i) in dense(&controls, 20) {
+        let offset = eval.eval(i) - center;
+        assert!((offset.xz().magnitude() - radius).abs() < radius * 1e-3);
+    }
+
+    // The surface faces the axis, rolling once per turn
+    for idx in 0..controls.len() - 1 {
+        let sample = track::sample_collection(&controls, idx as f32).unwrap();
+        let inward = (center - sample.position).xz().normalize();
+        let up = sample.up();
+        assert!((up.xz() - inward).magnitude() < 1e-3 && up.y.abs() < 1e-3);
+    }
+}
+
+#[test]
+fn flat_helix_is_a_loop() {
+    // No pitch leaves a full turn in the plane across the axis, ending where it began
+    let center = Point3::new(0., 0., 5.);
+    let (radius, axis) = (10., Vector3::z());
+    let controls = track::helix_about(center, axis, radius, 0., 1., 8);
+    assert_eq!(controls.len(), 9);
+    assert!(track::is_closed(&controls));
+
+    for (eval, i) in dense(&controls, 20) {
+        let offset = eval.eval(i) - center;
+        assert!(offset.z.abs() < 1e-5);
+        assert!((offset.magnitude() - radius).abs() < radius * 1e-3);
+    }
+
+    // The surface still faces the axis all the way round, including where it heads along -x
+    for idx in 0..controls.len() - 1 {
+        let sample = track::sample_collection(&controls, idx as f32).unwrap();
+        let inward = (center - sample.position).normalize();
+        assert!(sample.angle.is_finite());
+        assert!((sample.up() - inward).magnitude() < 1e-3);
+    }
+}
+
+#[test]
+fn clothoid_eases_curvature() {
+    let length = 30.;
+    for &(samples, tolerance) in &[(2, 0.011), (8, 0.0011)] {
+        let controls = track::clothoid_transition(0., 0.1, length, samples);
+        assert_eq!(controls.len(), samples + 1);
+        let measured = ArcLengthTable::new(&controls, 64).length();
+        assert!((measured - length).abs() < length * 2e-3);
+
+        // Curvature rises linearly with distance along the spiral
+        let step = length / samples as f32;
+        for (idx, pair) in controls.windows(2).enumerate() {
+            let eval = SegmentEval::new(&pair[0], &pair[1]);
+            for k in 0..=10 {
+                let i = k as f32 / 10.;
+                let expected = 0.1 * (idx as f32 + i) * step / length;
+                assert!((eval.curvature(i) - expected).abs() <= tolerance);
+            }
+        }
+
+        // Heading turns through the integral of the curvature
+        let heading = 0.1 * length / 2.;
+        let end = controls.last().unwrap().direction.normalize();
+        assert!((end - Vector3::new(heading.cos(), 0., heading.sin())).magnitude() < 1e-4);
+    }
+}
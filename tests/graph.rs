@@ -0,0 +1,45 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{TrackControl, TrackGraph};
+
+/// A straight lead-in to a switch, which forks to the left (-z) and right (+z)
+fn fork() -> TrackGraph {
+    let mut graph = TrackGraph::new();
+    let x = Vector3::new(10., 0., 0.);
+    let start = graph.add_node(TrackControl::new(Point3::origin(), x, 0.));
+    let switch = graph.add_node(TrackControl::new(Point3::new(20., 0., 0.), x, 0.));
+    let left = graph.add_node(TrackControl::new(Point3::new(40., 0., -10.), x, 0.));
+    let right = graph.add_node(TrackControl::new(Point3::new(40., 0., 10.), x, 0.));
+    graph.add_edge(start, switch);
+    graph.add_edge(switch, left);
+    graph.add_edge(switch, right);
+    graph
+}
+
+#[test]
+fn follower_takes_chosen_branch() {
+    let graph = fork();
+    assert_eq!(graph.outgoing(1), [1, 2]);
+    for &(choice, side) in &[(0, -1.), (1, 1.)] {
+        let mut junctions = Vec::new();
+        let samples: Vec<_> = graph
+            .follower(0, 0.5, |node, outgoing: &[usize]| {
+                junctions.push((node, outgoing.to_vec()));
+                choice
+            })
+            .collect();
+        assert_eq!(junctions, [(1, vec![1, 2])]);
+
+        // Runs past the switch to the chosen branch's dead end
+        let last = samples.last().unwrap();
+        assert!((last.position - Point3::new(40., 0., 10. * side)).magnitude() < 1.);
+        assert!(samples.iter().all(|s| s.position.z * side >= -1e-4));
+    }
+}
+
+#[test]
+fn follower_stops_on_bad_choice() {
+    let graph = fork();
+    let follower = graph.follower(0, 0.5, |_, _| 2);
+    assert!(follower.last().unwrap().position.x <= 20.);
+    assert!(graph.sample(3, 0.).is_none());
+}
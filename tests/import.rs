@@ -0,0 +1,54 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, Plane, TrackControl};
+
+/// A winding track on the ground plane, with uneven handle lengths through its middle
+fn ground_track() -> Vec<TrackControl> {
+    vec![
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl {
+            in_length: 0.5,
+            out_length: 1.5,
+            ..TrackControl::new(Point3::new(30., 0., 20.), Vector3::new(4., 0., 8.), 0.)
+        },
+        TrackControl::new(Point3::new(10., 0., 40.), Vector3::new(-10., 0., 2.), 0.),
+    ]
+}
+
+/// Whether two sets of controls trace the same curve
+fn same_curve(a: &[TrackControl], b: &[TrackControl]) -> bool {
+    a.len() == b.len()
+        && (0..(a.len() - 1) * 20).all(|k| {
+            let i = k as f32 / 20.;
+            let a = track::sample_collection(a, i).unwrap();
+            let b = track::sample_collection(b, i).unwrap();
+            (a.position - b.position).magnitude() < 1e-3
+        })
+}
+
+#[test]
+fn svg_round_trip() {
+    let controls = ground_track();
+    let mut d = format!("M {} {}", controls[0].position.x, controls[0].position.z);
+    for pair in controls.windows(2) {
+        let (a, b) = (pair[0].front_ctrlp(), pair[1].back_ctrlp());
+        let p = pair[1].position;
+        d += &format!(" C {} {} {} {} {} {}", a.x, a.z, b.x, b.z, p.x, p.z);
+    }
+
+    let imported = track::from_svg_path(&d, Plane::XZ).unwrap();
+    assert!(same_curve(&controls, &imported));
+    assert!((imported[1].in_length - 0.5).abs() < 1e-4);
+    assert!((imported[1].out_length - 1.5).abs() < 1e-4);
+}
+
+#[test]
+fn csv_round_trip() {
+    let controls = vec![
+        TrackControl::new(Point3::new(0.1, -2., 3.5), Vector3::new(10., 0.3, 0.), 0.25),
+        TrackControl::new(Point3::new(20., 5., 10.), Vector3::new(0., 2., 10.), -1.1),
+        TrackControl::new(Point3::new(1e-3, 5e4, 30.), Vector3::new(-10., 0., 0.7), 3.),
+    ];
+    let mut csv = Vec::new();
+    track::to_csv(&controls, &mut csv).unwrap();
+    assert_eq!(track::from_csv(&csv[..]).unwrap(), controls);
+}
@@ -0,0 +1,41 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, ArcLengthTable, TrackControl};
+
+/// A loop which swings round and passes back across its own start, `height` above it
+fn loop_back(height: f32) -> Vec<TrackControl> {
+    vec![
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(30., 0., 15.), Vector3::new(0., 0., 10.), 0.),
+        TrackControl::new(
+            Point3::new(15., height, 30.),
+            Vector3::new(-10., 0., 0.),
+            0.,
+        ),
+        TrackControl::new(
+            Point3::new(10., height, -15.),
+            Vector3::new(0., 0., -10.),
+            0.,
+        ),
+    ]
+}
+
+#[test]
+fn crossing_found_once() {
+    let controls = loop_back(0.);
+    let crossings = track::self_intersections(&controls, 0.5, 0.1);
+    assert_eq!(crossings.len(), 1);
+
+    // Both distances lead to the same spot on the ground
+    let (a, b) = crossings[0];
+    assert!(a < b);
+    let table = ArcLengthTable::new(&controls, 64);
+    let a = track::sample_at_distance(&controls, &table, a).unwrap();
+    let b = track::sample_at_distance(&controls, &table, b).unwrap();
+    assert!((a.position.xz() - b.position.xz()).magnitude() < 0.5);
+}
+
+#[test]
+fn bridges_are_not_crossings() {
+    assert!(track::self_intersections(&loop_back(5.), 0.5, 1.).is_empty());
+    assert_eq!(track::self_intersections(&loop_back(5.), 0.5, 10.).len(), 1);
+}
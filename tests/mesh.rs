@@ -0,0 +1,244 @@
+mod common;
+
+use common::straight;
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::mesh::{self, Vertex};
+use twisty_beziers::track::{TrackControl, TrackFollower};
+
+/// Counter-clockwise face normal of each triangle
+fn face_normals(vertices: &[Vertex], indices: &[u32]) -> Vec<Vector3<f32>> {
+    indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] =
+                [tri[0], tri[1], tri[2]].map(|i| Vector3::from(vertices[i as usize].pos));
+            (b - a).cross(&(c - a))
+        })
+        .collect()
+}
+
+#[test]
+fn walls_face_inward() {
+    let controls = straight();
+    let rows = TrackFollower::new(&controls, 1.).count();
+    let (vertices, indices) = mesh::walls(&controls, 2., 1., 1., true, true, [1.; 3]);
+    assert_eq!(vertices.len(), rows * 4);
+    assert_eq!(indices.len(), (rows - 1) * 2 * 6);
+    assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+
+    // The track's right is +z, so inward is toward z = 0 on either side
+    for (tri, face) in indices
+        .chunks_exact(3)
+        .zip(face_normals(&vertices, &indices))
+    {
+        let vertex = vertices[tri[0] as usize];
+        let normal = Vector3::from(vertex.normal);
+        assert!(vertex.pos[2] * normal.z < 0.);
+        assert!(face.dot(&normal) > 0.);
+        assert!((normal.magnitude() - 1.).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn walls_per_side() {
+    let controls = straight();
+    let (both, both_indices) = mesh::walls(&controls, 2., 1., 1., true, true, [1.; 3]);
+    let (left, left_indices) = mesh::walls(&controls, 2., 1., 1., true, false, [1.; 3]);
+    assert_eq!(left.len() * 2, both.len());
+    assert_eq!(left_indices.len() * 2, both_indices.len());
+    assert!(left.iter().all(|v| v.pos[2] < 0.));
+    let (_, neither) = mesh::walls(&controls, 2., 1., 1., false, false, [1.; 3]);
+    assert!(neither.is_empty());
+}
+
+#[test]
+fn wall_caps_face_outward() {
+    let controls = straight();
+    let (walls, _) = mesh::walls(&controls, 2., 1., 1., true, true, [1.; 3]);
+    let (vertices, indices) = mesh::wall_caps(&controls, 2., 1., 1., [1.; 3]);
+    assert_eq!((vertices.len(), indices.len()), (8, 12));
+
+    // Each cap closes the walls' first or last row, facing away from the track along x
+    let last_row = &walls[walls.len() - 4..];
+    for (cap, row, outward) in [
+        (&vertices[..4], &walls[..4], -1.),
+        (&vertices[4..], last_row, 1.),
+    ] {
+        for corner in row {
+            assert!(cap.iter().any(|v| v.pos == corner.pos));
+        }
+        assert!(cap.iter().all(|v| v.normal == [outward, 0., 0.]));
+    }
+    for (idx, face) in face_normals(&vertices, &indices).iter().enumerate() {
+        let outward = if idx < 2 { -1. } else { 1. };
+        assert!((face.normalize() - Vector3::x() * outward).magnitude() < 1e-4);
+    }
+
+    // A loop has no ends to cap
+    let mut closed = controls.clone();
+    closed.push(TrackControl::new(
+        Point3::new(15., 0., 30.),
+        Vector3::new(-10., 0., 0.),
+        0.,
+    ));
+    closed.push(closed[0]);
+    let (vertices, indices) = mesh::wall_caps(&closed, 2., 1., 1., [1.; 3]);
+    assert!(vertices.is_empty() && indices.is_empty());
+}
+
+/// Unit cube of 8 vertices and 12 triangles
+fn cube() -> (Vec<Vertex>, Vec<u32>) {
+    let vertices = (0..8)
+        .map(|i| {
+            let corner = [i & 1, (i >> 1) & 1, (i >> 2) & 1].map(|c| c as f32);
+            Vertex::new(corner, [1.; 3])
+        })
+        .collect();
+    let faces = [
+        [0, 1, 3, 2],
+        [4, 6, 7, 5],
+        [0, 4, 5, 1],
+        [2, 3, 7, 6],
+        [0, 2, 6, 4],
+        [1, 5, 7, 3],
+    ];
+    let indices = faces
+        .iter()
+        .flat_map(|&[a, b, c, d]| [a, b, c, a, c, d])
+        .collect();
+    (vertices, indices)
+}
+
+#[test]
+fn merge_two_cubes() {
+    let (vertices, indices) = mesh::merge(&[cube(), cube()]);
+    assert_eq!(vertices.len(), 16);
+    assert_eq!(indices.len() / 3, 24);
+    assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+
+    // The second cube's triangles are the first's, offset past its vertices
+    let (first, second) = indices.split_at(indices.len() / 2);
+    assert!(first.iter().zip(second).all(|(a, b)| a + 8 == *b));
+}
+
+#[test]
+fn obj_writes_positions_and_texture_coordinates() {
+    let vertices = vec![
+        Vertex::new([0., 0., 0.], [0., 0., 1.]),
+        Vertex::new([1., 0., 0.], [1., 0., 1.]),
+        Vertex::new([0., 1., 0.], [0., 1., 1.]).with_normal([0., 0., 1.]),
+    ];
+    let obj = |texture_coords| {
+        let mut obj = Vec::new();
+        mesh::write_obj(&vertices, &[0, 1, 2], texture_coords, &mut obj).unwrap();
+        String::from_utf8(obj).unwrap()
+    };
+
+    // Positions alone, never the color slot
+    let plain = obj(false);
+    let mut positions = plain.lines().filter(|l| l.starts_with("v "));
+    assert!(positions.all(|l| l.split(' ').count() == 4));
+    assert!(!plain.contains("vt "));
+    assert_eq!(plain.lines().last(), Some("f 1//1 2//2 3//3"));
+
+    let textured = obj(true);
+    assert!(textured.contains("vt 1 0 1\n"));
+    assert_eq!(textured.lines().last(), Some("f 1/1/1 2/2/2 3/3/3"));
+}
+
+#[test]
+fn weld_averages_normals() {
+    // Two triangles meeting at a right angle along the edge from (0, 0, 0) to (1, 0, 0), each
+    // with its own copy of the edge's vertices
+    let up = [0., 1., 0.];
+    let side = [0., 0., 1.];
+    let mut vertices = vec![
+        Vertex::new([0., 0., 0.], [1.; 3]).with_normal(up),
+        Vertex::new([1., 0., 0.], [1.; 3]).with_normal(up),
+        Vertex::new([0., 0., -1.], [1.; 3]).with_normal(up),
+        Vertex::new([0., 0., 0.], [1.; 3]).with_normal(side),
+        Vertex::new([0., 1., 0.], [1.; 3]).with_normal(side),
+        Vertex::new([1., 0., 0.], [1.; 3]).with_normal(side),
+        // A sliver which collapses onto the corner
+        Vertex::new([1e-4, 0., 0.], [1.; 3]),
+        Vertex::new([0., 1e-4, 0.], [1.; 3]),
+    ];
+    let mut indices = vec![0, 1, 2, 3, 4, 5, 6, 7, 0];
+    mesh::weld(&mut vertices, &mut indices, 1e-3);
+
+    assert_eq!(vertices.len(), 4);
+    assert_eq!(indices, [0, 1, 2, 0, 3, 1]);
+    let shared = Vector3::from(vertices[0].normal);
+    assert!((shared - Vector3::new(0., 1., 1.).normalize()).magnitude() < 1e-4);
+    assert_eq!(vertices[2].normal, up);
+    assert_eq!(vertices[3].normal, side);
+}
+
+#[test]
+#[should_panic]
+fn tess_indices_overflow_panics() {
+    // 3 vertices per row puts the last row past what u16 can address
+    let _ = mesh::tess_indices(u16::MAX as usize / 3 + 2, 1);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn tess_path_par_matches_serial() {
+    let controls = vec![
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(20., 5., 10.), Vector3::new(0., 2., 10.), 0.4),
+        TrackControl::new(Point3::new(0., 5., 30.), Vector3::new(-10., 0., 0.), -0.2),
+    ];
+    let lane_color: &(dyn Fn(i32, f32) -> [f32; 3] + Sync) = &|lane, v| [lane as f32, v, 0.];
+    for &color in &[None, Some(lane_color)] {
+        let serial = mesh::track_tess_path(&controls, 4, 8., 0.5, color.map(|f| f as _));
+        let parallel = mesh::track_tess_path_par(&controls, 4, 8., 0.5, color);
+        assert_eq!(serial, parallel);
+    }
+}
+
+/// A track snaking along x, long enough that its surface needs more than u16 indices
+fn long_track() -> Vec<TrackControl> {
+    (0..=120)
+        .map(|idx| {
+            let phase = idx as f32 * 0.7;
+            let position = Point3::new(idx as f32 * 20., phase.sin() * 8., phase.cos() * 15.);
+            let direction = Vector3::new(7., phase.cos() * 2., -phase.sin() * 4.);
+            TrackControl::new(position, direction, phase.sin() * 0.6)
+        })
+        .collect()
+}
+
+#[test]
+fn mesh_chunks_fit_u16_and_join() {
+    let controls = long_track();
+    let row_len = 17;
+    let rows = TrackFollower::new(&controls, 0.5).count();
+    assert!(rows * row_len > u16::MAX as usize + 1);
+
+    let mut surfaces = Vec::new();
+    for &max_vertices in &[usize::MAX, 1000] {
+        let chunks: Vec<_> = mesh::MeshChunker::new(&controls, 8, 8., 0.5, max_vertices).collect();
+        assert!(chunks.len() > 1);
+        for (vertices, indices) in &chunks {
+            assert!(vertices.len() <= (u16::MAX as usize + 1).min(max_vertices));
+            assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+        }
+
+        // Each chunk starts with the row the last one ended on, and together they are the whole
+        let mut joined = chunks[0].0.clone();
+        for pair in chunks.windows(2) {
+            let (before, after) = (&pair[0].0, &pair[1].0);
+            assert_eq!(before[before.len() - row_len..], after[..row_len]);
+            joined.extend_from_slice(&after[row_len..]);
+        }
+        assert_eq!(joined.len(), rows * row_len);
+        surfaces.push(joined);
+    }
+    assert_eq!(surfaces[0], surfaces[1]);
+
+    // A track short enough for a single chunk matches the unchunked surface
+    let short = &controls[..3];
+    let chunks: Vec<_> = mesh::MeshChunker::new(short, 8, 8., 0.5, usize::MAX).collect();
+    assert_eq!(chunks, [mesh::track_tess_path(short, 8, 8., 0.5, None)]);
+}
@@ -0,0 +1,36 @@
+use rosc::{OscMessage, OscPacket, OscType};
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+use twisty_beziers::controls::{OscControls, TwoAxisControls};
+
+/// Poll `controls` until they read `expected`, failing after a second
+fn wait_for(controls: &mut impl TwoAxisControls, expected: (f32, f32)) {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while controls.axes().unwrap() != expected {
+        assert!(Instant::now() < deadline, "Axes never read {:?}", expected);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn osc_maps_range_onto_axes() {
+    let addr = ("127.0.0.1", 57301);
+    let mut controls = OscControls::new(addr, "/x", "/y", (0., 10.)).unwrap();
+    assert_eq!(controls.axes().unwrap(), (0., 0.));
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let send = |address: &str, value: f32| {
+        let packet = OscPacket::Message(OscMessage {
+            addr: address.into(),
+            args: vec![OscType::Float(value)],
+        });
+        let datagram = rosc::encoder::encode(&packet).unwrap();
+        sender.send_to(&datagram, addr).unwrap();
+    };
+
+    // Out of range values are clamped, and other addresses ignored
+    send("/x", 7.5);
+    send("/y", 20.);
+    send("/z", 0.);
+    wait_for(&mut controls, (0.5, 1.));
+}
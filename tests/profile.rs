@@ -0,0 +1,121 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, ArcLengthTable, ProfiledFollower, TrackControl};
+
+const MAX_ACCEL: f32 = 2.;
+const MAX_DECEL: f32 = 4.;
+const MAX_LATERAL: f32 = 3.;
+
+/// A straight run into a tight hairpin and back out
+fn hairpin() -> Vec<TrackControl> {
+    vec![
+        TrackControl::new(Point3::origin(), Vector3::new(20., 0., 0.), 0.),
+        TrackControl::new(Point3::new(60., 0., 0.), Vector3::new(5., 0., 0.), 0.),
+        TrackControl::new(Point3::new(60., 0., 10.), Vector3::new(-5., 0., 0.), 0.),
+        TrackControl::new(Point3::new(0., 0., 10.), Vector3::new(-20., 0., 0.), 0.),
+    ]
+}
+
+#[test]
+fn profile_respects_limits() {
+    let controls = hairpin();
+    let table = ArcLengthTable::new(&controls, 64);
+    let limit = |d| track::speed_limit(&controls, &table, MAX_LATERAL, d);
+    let follower = ProfiledFollower::new(&controls, limit, MAX_ACCEL, MAX_DECEL, 0.05);
+
+    let profile = follower.speed_profile();
+    assert_eq!(profile[0].1, 0.);
+    for &(d, speed) in &profile {
+        assert!(speed <= limit(d) * 1.001);
+    }
+
+    // Within each interval, v1^2 = v0^2 + 2 a ds for the acceleration a used
+    for pair in profile.windows(2) {
+        let ((d0, v0), (d1, v1)) = (pair[0], pair[1]);
+        let accel = (v1 * v1 - v0 * v0) / (2. * (d1 - d0));
+        assert!((-MAX_DECEL * 1.001..=MAX_ACCEL * 1.001).contains(&accel));
+    }
+}
+
+#[test]
+fn follower_speed_changes_within_bounds() {
+    let controls = hairpin();
+    let table = ArcLengthTable::new(&controls, 64);
+    let limit = |d| track::speed_limit(&controls, &table, MAX_LATERAL, d);
+    let dt = 0.05;
+    let mut follower = ProfiledFollower::new(&controls, limit, MAX_ACCEL, MAX_DECEL, dt);
+    assert!(follower.duration().is_finite());
+
+    // Speed reads zero once past the end, which isn't braking
+    let mut last = follower.speed();
+    while follower.next().is_some() && follower.time <= follower.duration() {
+        let speed = follower.speed();
+        let accel = (speed - last) / dt;
+        assert!((-MAX_DECEL * 1.01..=MAX_ACCEL * 1.01).contains(&accel));
+        last = speed;
+    }
+}
+
+#[test]
+fn short_tracks_go_nowhere() {
+    let one = [TrackControl::new(Point3::origin(), Vector3::x(), 0.)];
+    for controls in [&one[..0], &one[..]] {
+        let mut follower = ProfiledFollower::new(controls, |_| 10., 1., 1., 0.1);
+        assert_eq!(follower.speed(), 0.);
+        assert!(follower.next().is_none());
+    }
+}
+
+#[test]
+fn no_acceleration_stays_at_start() {
+    let controls = hairpin();
+    let mut follower = ProfiledFollower::new(&controls, |_| 10., 0., 1., 0.1);
+    assert_eq!(follower.duration(), f32::INFINITY);
+    for sample in follower.by_ref().take(100) {
+        assert!((sample.position - controls[0].position).magnitude() < 1e-4);
+    }
+    assert_eq!(follower.speed(), 0.);
+}
+
+#[test]
+fn lap_time_on_a_straight() {
+    let controls = [
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(30., 0., 0.), Vector3::new(10., 0., 0.), 0.),
+    ];
+    assert!((track::lap_time(&controls, &[(0., 6.)]) - 5.).abs() < 1e-3);
+    assert!((track::lap_time(&controls, &[(10., 6.), (20., 6.)]) - 5.).abs() < 1e-3);
+
+    // Speeding up steadily from 5 to 15 averages 10
+    assert!((track::lap_time(&controls, &[(0., 5.), (30., 15.)]) - 3.).abs() < 1e-3);
+
+    // Never arrives if it stops partway
+    let stopping = [(0., 5.), (15., 0.)];
+    assert_eq!(track::lap_time(&controls, &stopping), f32::INFINITY);
+    assert_eq!(track::lap_time(&controls, &[]), f32::INFINITY);
+}
+
+#[test]
+fn lap_time_steps_at_shared_distances() {
+    let controls = [
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(30., 0., 0.), Vector3::new(10., 0., 0.), 0.),
+    ];
+
+    // Halving the speed halfway along, without easing into it
+    let step = [(0., 6.), (15., 6.), (15., 3.), (30., 3.)];
+    assert!((track::lap_time(&controls, &step) - 7.5).abs() < 1e-3);
+
+    // Repeated points change nothing
+    let repeated = [(0., 6.), (0., 6.), (15., 6.), (15., 6.)];
+    assert!((track::lap_time(&controls, &repeated) - 5.).abs() < 1e-3);
+}
+
+#[test]
+fn lap_time_matches_follower() {
+    let controls = hairpin();
+    let table = ArcLengthTable::new(&controls, 64);
+    let limit = |d| track::speed_limit(&controls, &table, MAX_LATERAL, d);
+    let follower = ProfiledFollower::new(&controls, limit, MAX_ACCEL, MAX_DECEL, 0.05);
+    let time = track::lap_time(&controls, &follower.speed_profile());
+    assert!((time - follower.duration()).abs() < follower.duration() * 1e-3);
+}
@@ -0,0 +1,118 @@
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, EndCondition, SegmentEval, TrackControl};
+
+/// Uneven controls with deliberately poor directions and handle lengths
+fn lumpy() -> Vec<TrackControl> {
+    let points = [
+        (0., 0., 0.),
+        (10., 4., 3.),
+        (35., -2., 10.),
+        (40., 8., 30.),
+        (20., 0., 45.),
+    ];
+    points
+        .iter()
+        .enumerate()
+        .map(|(idx, &(x, y, z))| TrackControl {
+            in_length: 0.5 + idx as f32 * 0.3,
+            ..TrackControl::new(Point3::new(x, y, z), Vector3::new(1., 5., -2.), 0.)
+        })
+        .collect()
+}
+
+/// Evaluators for each segment of the track
+fn segments(controls: &[TrackControl]) -> Vec<SegmentEval> {
+    controls
+        .windows(2)
+        .map(|pair| SegmentEval::new(&pair[0], &pair[1]))
+        .collect()
+}
+
+fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+    (a - b).magnitude() <= 1e-3 * a.magnitude().max(1.)
+}
+
+#[test]
+fn smooth_c2_is_continuous() {
+    for &end in &[EndCondition::Natural, EndCondition::Clamped] {
+        let before = lumpy();
+        let mut controls = before.clone();
+        track::smooth_c2(&mut controls, end);
+        for (before, after) in before.iter().zip(&controls) {
+            assert_eq!(before.position, after.position);
+        }
+
+        let segments = segments(&controls);
+        for pair in segments.windows(2) {
+            assert!(close(pair[0].eval_deriv(1.), pair[1].eval_deriv(0.)));
+            assert!(close(pair[0].eval_deriv2(1.), pair[1].eval_deriv2(0.)));
+        }
+
+        let (first, last) = (&segments[0], segments.last().unwrap());
+        match end {
+            EndCondition::Natural => {
+                assert!(close(first.eval_deriv2(0.), Vector3::zeros()));
+                assert!(close(last.eval_deriv2(1.), Vector3::zeros()));
+            }
+            EndCondition::Clamped => {
+                let finish = before.last().unwrap();
+                let incoming = finish.direction * finish.in_length;
+                assert!(close(first.eval_deriv(0.), before[0].direction * 3.));
+                assert!(close(last.eval_deriv(1.), incoming * 3.));
+            }
+        }
+    }
+}
+
+/// Distance from `point` to the nearest line of `polyline`
+fn polyline_distance(polyline: &[Point3<f32>], point: Point3<f32>) -> f32 {
+    polyline
+        .windows(2)
+        .map(|pair| {
+            let line = pair[1] - pair[0];
+            let t = ((point - pair[0]).dot(&line) / line.magnitude_squared()).clamp(0., 1.);
+            (point - (pair[0] + line * t)).magnitude()
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+#[test]
+fn flatten_stays_within_tolerance() {
+    let controls = lumpy();
+    let mut last_len = 0;
+    for &tolerance in &[1., 0.1, 0.01] {
+        let points = track::flatten(&controls, tolerance);
+        assert!(points.len() > last_len);
+        last_len = points.len();
+        for control in &controls {
+            assert!(points.contains(&control.position));
+        }
+        for segment in segments(&controls) {
+            for k in 0..=100 {
+                let point = segment.eval(k as f32 / 100.);
+                assert!(polyline_distance(&points, point) <= tolerance * 1.01);
+            }
+        }
+    }
+}
+
+#[test]
+fn handle_lengths_scale_each_side() {
+    let direction = Vector3::new(4., 1., 2.);
+    let control = TrackControl::builder()
+        .position(Point3::new(10., 0., 0.))
+        .direction(direction)
+        .in_length(0.5)
+        .out_length(2.)
+        .build();
+    assert_eq!(control.back_ctrlp(), control.position - direction * 0.5);
+    assert_eq!(control.front_ctrlp(), control.position + direction * 2.);
+
+    // The tangents entering and leaving the control are scaled separately, but stay parallel
+    let before = TrackControl::new(Point3::origin(), Vector3::x() * 3., 0.);
+    let after = TrackControl::new(Point3::new(20., 5., 5.), Vector3::x() * 3., 0.);
+    let entering = track::spline_deriv(&before, &control, 1.);
+    let leaving = track::spline_deriv(&control, &after, 0.);
+    assert!(close(entering, direction * 1.5));
+    assert!(close(leaving, direction * 6.));
+}
@@ -0,0 +1,106 @@
+use nalgebra::{Point3, Vector3};
+use proptest::prelude::*;
+use twisty_beziers::track::{self, TrackControl};
+
+/// Samples taken along each segment when comparing curves
+const SAMPLES: usize = 32;
+
+fn control(position: [f32; 3], direction: [f32; 3], angle: f32) -> TrackControl {
+    TrackControl::new(Point3::from(position), Vector3::from(direction), angle)
+}
+
+/// Whether `a` and `b` agree to within a tolerance relative to their size
+fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+    (a - b).magnitude() <= 1e-3 * (1. + a.magnitude().max(b.magnitude()))
+}
+
+proptest! {
+    #[test]
+    fn split_segment_preserves_curve(
+        p0 in proptest::array::uniform3(-100f32..100.),
+        d0 in proptest::array::uniform3(-50f32..50.),
+        p1 in proptest::array::uniform3(-100f32..100.),
+        d1 in proptest::array::uniform3(-50f32..50.),
+        in_length in 0.25f32..4.,
+        out_length in 0.25f32..4.,
+        t in 0.01f32..0.99,
+    ) {
+        let begin = TrackControl { out_length, ..control(p0, d0, 0.) };
+        let end = TrackControl { in_length, ..control(p1, d1, 0.) };
+        let (first, middle, last) = track::split_segment(&begin, &end, t);
+
+        for k in 0..=SAMPLES {
+            let i = k as f32 / SAMPLES as f32;
+
+            // The first half covers [0, t] of the original segment, and the second [t, 1]
+            let halves = [
+                (&first, &middle, t * i, t),
+                (&middle, &last, t + (1. - t) * i, 1. - t),
+            ];
+            for (a, b, original_i, scale) in halves {
+                let expected = track::spline(&begin, &end, original_i);
+                let actual = track::spline(a, b, i);
+                prop_assert!(
+                    close(expected.coords, actual.coords),
+                    "position at {}: {:?} != {:?}", original_i, expected, actual
+                );
+
+                let expected = track::spline_deriv(&begin, &end, original_i) * scale;
+                let actual = track::spline_deriv(a, b, i);
+                prop_assert!(
+                    close(expected, actual),
+                    "derivative at {}: {:?} != {:?}", original_i, expected, actual
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn concat_of_split_round_trips(
+        p0 in proptest::array::uniform3(-100f32..100.),
+        p1 in proptest::array::uniform3(-100f32..100.),
+        p2 in proptest::array::uniform3(-100f32..100.),
+        p3 in proptest::array::uniform3(-100f32..100.),
+        d0 in proptest::array::uniform3(-50f32..50.),
+        d1 in proptest::array::uniform3(-50f32..50.),
+        d2 in proptest::array::uniform3(-50f32..50.),
+        d3 in proptest::array::uniform3(-50f32..50.),
+        segment in 0usize..3,
+        t in 0.01f32..0.99,
+    ) {
+        let controls = vec![
+            control(p0, d0, 0.),
+            control(p1, d1, 0.5),
+            control(p2, d2, -0.5),
+            control(p3, d3, 1.),
+        ];
+
+        // Splitting at a control joins back to the same controls
+        let (before, after) = track::split(&controls, segment as f32).unwrap();
+        prop_assert_eq!(track::concat(&before, &after), controls.clone());
+
+        // Splitting within a segment adds a control, but traces the same curve
+        let (before, after) = track::split(&controls, segment as f32 + t).unwrap();
+        let joined = track::concat(&before, &after);
+        prop_assert_eq!(joined.len(), controls.len() + 1);
+        for k in 0..(controls.len() - 1) * SAMPLES {
+            let i = k as f32 / SAMPLES as f32;
+            let local = i - segment as f32;
+            let joined_i = if local < 0. {
+                i
+            } else if local < t {
+                segment as f32 + local / t
+            } else if local < 1. {
+                segment as f32 + 1. + (local - t) / (1. - t)
+            } else {
+                i + 1.
+            };
+            let expected = track::sample_collection(&controls, i).unwrap().position;
+            let actual = track::sample_collection(&joined, joined_i).unwrap().position;
+            prop_assert!(
+                close(expected.coords, actual.coords),
+                "position at {}: {:?} != {:?}", i, expected, actual
+            );
+        }
+    }
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::straight;
+use nalgebra::{Point3, Vector3};
+use twisty_beziers::track::{self, ArcLengthTable, Track, TrackControl};
+
+#[test]
+fn track_measures_and_invalidates() {
+    let mut track = Track::new(straight());
+    assert!((track.length() - 30.).abs() < 1e-3);
+    let sample = track.sample_at_distance(12.).unwrap();
+    assert!((sample.position - Point3::new(12., 0., 0.)).magnitude() < 1e-2);
+    assert_eq!(
+        track.sample(0.5),
+        track::sample_collection(&straight(), 0.5)
+    );
+
+    // Extending the track is reflected in the cached measurements
+    track.push(TrackControl::new(
+        Point3::new(30., 0., 20.),
+        Vector3::new(0., 0., 10.),
+        0.,
+    ));
+    let table = ArcLengthTable::new(track.controls(), 64);
+    assert!(track.length() > 40.);
+    assert!((track.length() - table.length()).abs() < 1e-2);
+    track.controls_mut().truncate(2);
+    assert!((track.length() - 30.).abs() < 1e-3);
+}
+
+#[test]
+fn progress_along_track() {
+    let track = Track::new(straight());
+    for &d in &[0., 7.5, 15., 29.] {
+        // Off to the side and above the track
+        let point = Point3::new(d, 2., -3.);
+        assert!((track.progress(point) - d).abs() < 1e-2);
+    }
+    assert!(track.progress(Point3::new(-5., 0., 0.)).abs() < 1e-3);
+    assert!((track.progress(Point3::new(40., 0., 0.)) - 30.).abs() < 1e-3);
+    assert_eq!(Track::default().progress(Point3::origin()), 0.);
+}
+
+#[test]
+fn grade_of_a_ramp() {
+    // Rises 1 for every 2 along x
+    let direction = Vector3::new(10., 5., 0.);
+    let ramp = Track::new(vec![
+        TrackControl::new(Point3::origin(), direction, 0.),
+        TrackControl::new(Point3::new(40., 20., 0.), direction, 0.),
+    ]);
+    for &d in &[0., 10., ramp.length()] {
+        assert!((ramp.grade_at_distance(d) - 0.5).abs() < 1e-4);
+        assert!((ramp.pitch_at_distance(d) - 0.5f32.atan()).abs() < 1e-4);
+    }
+
+    // Straight up is clamped rather than infinite
+    let up = Vector3::new(0., 10., 0.);
+    let wall = Track::new(vec![
+        TrackControl::new(Point3::origin(), up, 0.),
+        TrackControl::new(Point3::new(0., 30., 0.), up, 0.),
+    ]);
+    assert_eq!(wall.grade_at_distance(15.), 1000.);
+    assert!((wall.pitch_at_distance(15.) - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+    assert_eq!(Track::default().grade_at_distance(0.), 0.);
+}
+
+#[test]
+fn sample_normalized_by_arc_length() {
+    // Segments of 10 and 30, so a quarter of the way is the middle control; t clamps to [0, 1]
+    let x = Vector3::new(10., 0., 0.);
+    let controls = vec![
+        TrackControl::new(Point3::origin(), x / 3., 0.),
+        TrackControl::new(Point3::new(10., 0., 0.), x, 0.),
+        TrackControl::new(Point3::new(40., 0., 0.), x, 0.),
+    ];
+    let track = Track::new(controls.clone());
+    for &(t, expected) in &[(-1., 0.), (0.25, 10.), (0.5, 20.), (2., 40.)] {
+        let sample = track.sample_normalized(t).unwrap();
+        assert!((sample.position.x - expected).abs() < 2e-2);
+        assert_eq!(Some(sample), track::sample_normalized(&controls, t));
+    }
+    assert!(Track::default().sample_normalized(0.5).is_none());
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::straight;
+use nalgebra::{Matrix4, Point3, UnitQuaternion, Vector3};
+use twisty_beziers::track::{self, TrackControl};
+
+fn track() -> Vec<TrackControl> {
+    vec![
+        TrackControl::new(Point3::origin(), Vector3::new(10., 0., 0.), 0.),
+        TrackControl::new(Point3::new(20., 5., 10.), Vector3::new(0., 2., 10.), 0.4),
+        TrackControl::new(Point3::new(0., 5., 30.), Vector3::new(-10., 0., 0.), -0.2),
+    ]
+}
+
+fn close(a: Point3<f32>, b: Point3<f32>) -> bool {
+    (a - b).magnitude() < 1e-3
+}
+
+#[test]
+fn transform_moves_the_whole_curve() {
+    let controls = track();
+    let rotation = UnitQuaternion::from_euler_angles(0.3, -1.1, 0.7);
+    let offset = Vector3::new(4., -2., 9.);
+    let m = Matrix4::new_translation(&offset) * rotation.to_homogeneous();
+    let moved = track::transform(&controls, &m);
+
+    for k in 0..40 {
+        let i = k as f32 / 20.;
+        let before = track::sample_collection(&controls, i).unwrap();
+        let after = track::sample_collection(&moved, i).unwrap();
+        assert!(close(after.position, m.transform_point(&before.position)));
+        assert!((after.angle - before.angle).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn translate_rotate_scale() {
+    let controls = track();
+    let offset = Vector3::new(1., 2., 3.);
+    for (before, after) in controls.iter().zip(track::translate(&controls, &offset)) {
+        assert!(close(after.position, before.position + offset));
+        assert_eq!(after.direction, before.direction);
+    }
+
+    let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 1.);
+    for (before, after) in controls.iter().zip(track::rotate(&controls, &rotation)) {
+        assert!(close(after.position, rotation * before.position));
+        assert!((after.direction - rotation * before.direction).magnitude() < 1e-4);
+        assert_eq!(after.angle, before.angle);
+    }
+
+    for (before, after) in controls.iter().zip(track::scale(&controls, 2.)) {
+        assert!(close(after.position, before.position * 2.));
+        assert!((after.direction - before.direction * 2.).magnitude() < 1e-4);
+        assert_eq!(after.width, before.width * 2.);
+    }
+}
+
+/// A straight along x, banked so that its surface faces partly toward +z
+fn banked_straight() -> Vec<TrackControl> {
+    straight()
+        .into_iter()
+        .map(|c| TrackControl { angle: 0.3, ..c })
+        .collect()
+}
+
+#[test]
+fn reverse_a_straight() {
+    // Heading along -x has no shortest rotation from +x, so it must still be oriented upright
+    let controls = banked_straight();
+    let reversed = track::reverse(&controls);
+    for k in 0..=20 {
+        let i = k as f32 / 20.;
+        let before = track::sample(&controls[0], &controls[1], 1. - i);
+        let after = track::sample(&reversed[0], &reversed[1], i);
+        assert!(close(after.position, before.position));
+        assert!((after.derivative + before.derivative).magnitude() < 1e-3);
+        assert!((after.up() - before.up()).magnitude() < 1e-4);
+    }
+
+    // Unbanked, the reversed track is upright with its right toward -z
+    let reversed = track::reverse(&straight());
+    let sample = track::sample(&reversed[0], &reversed[1], 0.5);
+    assert!(reversed.iter().all(|c| c.angle.abs() < 1e-6));
+    assert!((sample.up() - Vector3::y()).magnitude() < 1e-4);
+    assert!((sample.right() + Vector3::z()).magnitude() < 1e-4);
+}
+
+#[test]
+fn mirror_a_straight_across_yz() {
+    let controls = banked_straight();
+    let mirrored = track::mirror(&controls, Vector3::x(), Point3::origin());
+    let reflect = |v: Vector3<f32>| Vector3::new(-v.x, v.y, v.z);
+    for k in 0..=20 {
+        let i = k as f32 / 20.;
+        let before = track::sample(&controls[0], &controls[1], i);
+        let after = track::sample(&mirrored[0], &mirrored[1], i);
+        let p = before.position;
+        assert!(close(after.position, Point3::new(-p.x, p.y, p.z)));
+        assert!((after.derivative - reflect(before.derivative)).magnitude() < 1e-3);
+        assert!((after.up() - reflect(before.up())).magnitude() < 1e-4);
+    }
+}
+
+/// Whether two controls match, allowing for rounding in their directions
+fn same_control(a: &TrackControl, b: &TrackControl) -> bool {
+    let rounded = TrackControl {
+        direction: b.direction,
+        ..*a
+    };
+    (a.direction - b.direction).magnitude() < 1e-4 && rounded == *b
+}
+
+#[test]
+fn morph_endpoints_are_the_inputs() {
+    let a = track();
+    let b: Vec<_> = track::rotate(&a, &UnitQuaternion::from_euler_angles(0., 2., 0.))
+        .into_iter()
+        .map(|c| TrackControl {
+            angle: c.angle + 1.,
+            width: 3.,
+            out_length: 2.,
+            color: [0.5, 0., 1.],
+            ..c
+        })
+        .collect();
+
+    let start = track::morph(&a, &b, 0.).unwrap();
+    let end = track::morph(&a, &b, 1.).unwrap();
+    assert!(start.iter().zip(&a).all(|(m, a)| same_control(m, a)));
+    assert!(end.iter().zip(&b).all(|(m, b)| same_control(m, b)));
+
+    // Halfway, directions have turned halfway between the two
+    let middle = track::morph(&a, &b, 0.5).unwrap();
+    for ((a, b), m) in a.iter().zip(&b).zip(&middle) {
+        let angle = a.direction.angle(&b.direction);
+        assert!((a.direction.angle(&m.direction) - angle / 2.).abs() < 1e-3);
+        assert!((m.angle - (a.angle + b.angle) / 2.).abs() < 1e-5);
+    }
+    assert!(track::morph(&a, &b[1..], 0.5).is_err());
+}
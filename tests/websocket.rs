@@ -0,0 +1,34 @@
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use tungstenite::Message;
+use twisty_beziers::controls::{TwoAxisControls, WebSocketControls};
+
+/// Poll `controls` until they read `expected`, failing after a second
+fn wait_for(controls: &mut impl TwoAxisControls, expected: (f32, f32)) {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while controls.axes().unwrap() != expected {
+        assert!(Instant::now() < deadline, "Axes never read {:?}", expected);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn websocket_reads_latest_message() {
+    let port = 57302;
+    let mut controls = WebSocketControls::new(("127.0.0.1", port)).unwrap();
+    assert_eq!(controls.axes().unwrap(), (0., 0.));
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let url = format!("ws://127.0.0.1:{}", port);
+    let (mut socket, _) = match tungstenite::client(url.as_str(), stream) {
+        Ok(client) => client,
+        Err(_) => panic!("WebSocket handshake failed"),
+    };
+
+    // Values are clamped to [-1, 1]
+    let mut send = |text: &str| socket.write_message(Message::Text(text.into())).unwrap();
+    send(r#"{"x": 0.25, "y": -3}"#);
+    wait_for(&mut controls, (0.25, -1.));
+    send(r#"{"x": -0.5, "y": 0.75}"#);
+    wait_for(&mut controls, (-0.5, 0.75));
+}